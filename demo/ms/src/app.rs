@@ -1,11 +1,13 @@
-use std::{io, collections::HashMap, time::Duration, cell::RefCell};
+use std::{io, collections::HashMap, time::{Duration, Instant}, cell::RefCell};
+use indexmap::IndexMap;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use ratatui::DefaultTerminal;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use reqwest;
 use serde_json::json;
 use reqwest::header::{HeaderMap, HeaderValue};
 use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
 use ratatui::style::{Style, Color,palette::tailwind, Stylize};
 use ratatui::text::{Line, Span, Text};
 use ratatui::layout::{Alignment, Constraint, Layout, Rect};
@@ -13,11 +15,27 @@ use ratatui::buffer::Buffer;
 use ratatui::widgets::{Gauge, Widget,block::Title,Block,Borders, Padding, Paragraph};
 
 use crate::ui;
-use crate::docker::DockerManager;
+use crate::docker::{CancelHandle, ContainerAction, ContainerInfo, DockerManager, SetupAttempt, SetupError};
+use crate::grid::GridState;
+use crate::metadata::{self, ChainsSource, MetadataOptions};
+use crate::sql::{self, QueryError, QueryStats, SqlClient};
+use crate::config::Config;
+use crate::catalog::{Catalog, CatalogTable, TableId};
+use crate::finder::{Finder, FinderTarget};
+use crate::pull::{self, PullProgress};
+use crate::logs::LogView;
+use crate::ports::{PortConflict, Ports};
+use crate::editor::TextEditor;
+use crate::completion::Completion;
+use crate::manuscript::{self, ManuscriptBrowser, ManuscriptDraft, NamePrompt, PromptAction};
+use crate::jobs::{JobClient, JobExceptions, JobSummary, JobsView};
+use crate::metrics::{JobDashboard, JobMetrics};
 
 #[derive(Debug)]
 pub struct App {
     pub chains: Vec<Chain>,            // 保存所有链信息
+    pub chains_source: ChainsSource,
+    pub catalog: Catalog,
     pub selected_chain_index: usize,    // 记录当前选中的链索引
     pub selected_table_index: Option<usize>,  // 新增：当前选中的表索引
     pub show_tables: bool,                    // 新增：是否显示表列表
@@ -25,20 +43,50 @@ pub struct App {
     pub exit: bool,
     pub current_tab: usize,  // Add this line
     pub example_data: Option<ExampleData>,  // Add this line
-    pub sql_input: String,
+    pub samples: HashMap<TableId, SampleState>,  // 按 chain/table 缓存的示例数据
+    pub sql_editor: TextEditor,
+    pub completion: Option<Completion>,  // SQL 编辑器的补全弹窗
     pub show_sql_window: bool,
-    pub sql_cursor_position: usize,
+    pub keyboard_enhanced: bool,  // 终端能区分 Ctrl+Enter 和 Enter
     pub sql_result: Option<String>,  // To store the mock response
     pub saved_sql: Option<String>,  // Add this field to store saved SQL
     pub sql_executing: bool,
-    pub sql_error: Option<String>,
+    pub sql_error: Option<QueryError>,
+    pub sql_stats: Option<QueryStats>,
     pub sql_columns: Vec<Column>,
     pub sql_data: Vec<Vec<serde_json::Value>>,
+    pub results_grid: GridState,
+    pub example_grid: GridState,
+    pub grid_focused: bool,
+    pub finder: Option<Finder>,
+    pub highlighted_column: Option<usize>,
     sql_sender: Option<mpsc::Sender<Result<serde_json::Value, String>>>,
     sql_receiver: Option<mpsc::Receiver<Result<serde_json::Value, String>>>,
     pub sql_timer: u64,  // Add this field for the timer
-    pub docker_manager: DockerManager,
+    pub config: Config,
+    pub show_config: bool,
+    pub docker_manager: DockerManager,         // 当前环境，与 environments 中对应的一项共享端口状态
+    pub environments: Vec<DockerManager>,
+    pub active_environment: usize,
+    pub show_environments: bool,
+    pub environment_selected: usize,
     pub docker_status: Option<String>,
+    pub show_container: bool,
+    pub container: ContainerStatus,
+    pub container_message: Option<String>,           // 最近一次操作的结果
+    pub container_confirm: Option<ContainerAction>,  // 等待确认的操作
+    pub container_busy: Option<ContainerAction>,     // 正在执行的操作
+    pub container_expected: bool,                    // 是否应该在运行（setup 完成后）
+    pub container_died: bool,
+    container_polling: bool,
+    container_checked_at: Option<Instant>,
+    pub show_logs: bool,
+    pub logs: LogView,
+    log_task: Option<AbortHandle>,
+    pub show_jobs: bool,
+    pub jobs: JobsView,
+    jobs_polling: bool,
+    jobs_checked_at: Option<Instant>,
     pub docker_setup_in_progress: bool,
     pub docker_setup_timer: u64,  // Add this new field
     pub setup_progress: f64,  // Add this field
@@ -46,11 +94,17 @@ pub struct App {
     pub state: AppState,
     progress_columns: u16,
     pub progress1: f64,
+    pub pull_progress: Option<PullProgress>,
     pub progress_lines: RefCell<Vec<String>>,
-    pub should_cancel_setup: bool,  // Add this new field
+    pub setup_cancel: CancelHandle,  // 与后台 setup 任务共享的取消信号
     pub update_sender: Option<mpsc::Sender<AppUpdate>>,
     pub update_receiver: Option<mpsc::Receiver<AppUpdate>>,
     pub current_setup_step: Option<SetupStep>,  // 新增字段
+    pub setup_step_complete: bool,               // 当前步骤是否已经完成
+    pub setup_attempts: Vec<(SetupStep, SetupAttempt)>,  // 本次 setup 中失败的尝试
+    pub port_conflicts: Vec<PortConflict>,
+    pub manuscripts: ManuscriptBrowser,  // MANUSCRIPTS 页
+    pub draft: Option<ManuscriptDraft>,  // 正在生成的 manuscript
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -59,6 +113,7 @@ pub enum SetupState {
     InProgress,
     Complete,
     Failed(String),
+    Cancelled,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -73,6 +128,8 @@ impl Clone for App {
     fn clone(&self) -> Self {
         Self {
             chains: self.chains.clone(),
+            chains_source: self.chains_source.clone(),
+            catalog: self.catalog.clone(),
             selected_chain_index: self.selected_chain_index,
             selected_table_index: self.selected_table_index,
             show_tables: self.show_tables,
@@ -80,20 +137,50 @@ impl Clone for App {
             exit: self.exit,
             current_tab: self.current_tab,
             example_data: self.example_data.clone(),
-            sql_input: self.sql_input.clone(),
+            samples: self.samples.clone(),
+            sql_editor: self.sql_editor.clone(),
+            completion: self.completion.clone(),
             show_sql_window: self.show_sql_window,
-            sql_cursor_position: self.sql_cursor_position,
+            keyboard_enhanced: self.keyboard_enhanced,
             sql_result: self.sql_result.clone(),
             saved_sql: self.saved_sql.clone(),
             sql_executing: self.sql_executing,
             sql_error: self.sql_error.clone(),
+            sql_stats: self.sql_stats.clone(),
             sql_columns: self.sql_columns.clone(),
             sql_data: self.sql_data.clone(),
+            results_grid: self.results_grid.clone(),
+            example_grid: self.example_grid.clone(),
+            grid_focused: self.grid_focused,
+            finder: self.finder.clone(),
+            highlighted_column: self.highlighted_column,
             sql_sender: self.sql_sender.clone(),
             sql_receiver: None,  // Don't clone the receiver
             sql_timer: self.sql_timer,
+            config: self.config.clone(),
+            show_config: self.show_config,
             docker_manager: self.docker_manager.clone(),
+            environments: self.environments.clone(),
+            active_environment: self.active_environment,
+            show_environments: self.show_environments,
+            environment_selected: self.environment_selected,
             docker_status: self.docker_status.clone(),
+            show_container: self.show_container,
+            container: self.container.clone(),
+            container_message: self.container_message.clone(),
+            container_confirm: self.container_confirm,
+            container_busy: self.container_busy,
+            container_expected: self.container_expected,
+            container_died: self.container_died,
+            container_polling: self.container_polling,
+            container_checked_at: self.container_checked_at,
+            show_logs: self.show_logs,
+            logs: self.logs.clone(),
+            log_task: self.log_task.clone(),
+            show_jobs: self.show_jobs,
+            jobs: self.jobs.clone(),
+            jobs_polling: self.jobs_polling,
+            jobs_checked_at: self.jobs_checked_at,
             docker_setup_in_progress: self.docker_setup_in_progress,
             docker_setup_timer: self.docker_setup_timer,  // Initialize the timer
             setup_progress: self.setup_progress,
@@ -101,11 +188,17 @@ impl Clone for App {
             state: self.state,
             progress_columns: self.progress_columns,
             progress1: self.progress1,
+            pull_progress: self.pull_progress.clone(),
             progress_lines: RefCell::new(Vec::new()),
-            should_cancel_setup: self.should_cancel_setup,  // Clone the new field
+            setup_cancel: self.setup_cancel.clone(),
             update_sender: self.update_sender.clone(),
             update_receiver: None,
             current_setup_step: self.current_setup_step.clone(),
+            setup_step_complete: self.setup_step_complete,
+            setup_attempts: self.setup_attempts.clone(),
+            port_conflicts: self.port_conflicts.clone(),
+            manuscripts: self.manuscripts.clone(),
+            draft: self.draft.clone(),
         }
     }
 }
@@ -116,6 +209,23 @@ pub struct ExampleData {
     pub data: Vec<Vec<serde_json::Value>>,
 }
 
+// 示例数据的加载状态
+#[derive(Debug, Clone)]
+pub enum SampleState {
+    Loading,
+    Loaded(ExampleData),
+    Failed(String),
+}
+
+// 调试容器的最新状态
+#[derive(Debug, Clone)]
+pub enum ContainerStatus {
+    Unknown,
+    Missing,
+    Found(ContainerInfo),
+    Failed(String),
+}
+
 #[derive(Debug, Clone)]
 pub struct Column {
     pub name: String,
@@ -128,13 +238,15 @@ pub struct Chain {
     pub status: String,
     pub lastUpdate: String,
     pub time_ago: String,  // 新增字段存储计算好的时间差
-    pub dataDictionary: HashMap<String, Vec<DataDictionaryItem>>,
+    pub dataDictionary: IndexMap<String, Vec<DataDictionaryItem>>,  // 表名 → 字段列表，保持 API 返回的顺序
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct DataDictionaryItem {
     pub name: String,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub dataType: String,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub description: String,
 }
 
@@ -145,6 +257,21 @@ pub enum AppUpdate {
     SetupProgress(SetupStep, SetupStepStatus),  // 修改这一行
     SetupComplete,
     SetupFailed(String, SetupStep),  // 修改这一行，添加失败的步骤
+    SetupCancelled(String),
+    SetupAttemptFailed(SetupStep, SetupAttempt),
+    PortsInUse(Vec<PortConflict>),
+    PortsChanged(Ports),
+    SampleLoaded(TableId, Result<ExampleData, String>),
+    PullProgress(PullProgress),
+    ContainerStatus(String, Result<Option<ContainerInfo>, String>),  // 容器名 → 状态
+    ContainerActionDone(ContainerAction, Result<(), String>),
+    LogLines(String, Vec<String>),
+    LogsEnded(String, Option<String>),
+    JobsLoaded(String, Result<Vec<JobSummary>, String>),  // job manager 地址 → 作业列表
+    JobSubmitted(String, Result<String, String>),         // manuscript 名 → 作业 id
+    JobCancelled(String, Result<(), String>),
+    JobExceptionsLoaded(String, Result<JobExceptions, String>),
+    JobMetricsLoaded(String, Result<JobMetrics, String>),  // 作业 id → 指标
 }
 
 // 新增状态枚举
@@ -167,7 +294,26 @@ pub enum SetupStep {
 }
 
 impl SetupStep {
-    fn as_str(&self) -> &'static str {
+    pub const ALL: [SetupStep; 5] = [
+        SetupStep::CheckingDocker,
+        SetupStep::PullingImage,
+        SetupStep::StartingContainer,
+        SetupStep::ConfiguringNetwork,
+        SetupStep::VerifyingSetup,
+    ];
+
+    // 配置文件中 [setup.<key>] 的名字
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            SetupStep::CheckingDocker => "setup.docker",
+            SetupStep::PullingImage => "setup.pull",
+            SetupStep::StartingContainer => "setup.start",
+            SetupStep::ConfiguringNetwork => "setup.network",
+            SetupStep::VerifyingSetup => "setup.verify",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
         match self {
             SetupStep::CheckingDocker => "Checking Docker installation",
             SetupStep::PullingImage => "Pulling required images",
@@ -179,18 +325,25 @@ impl SetupStep {
 }
 
 impl App {
-    pub async fn new() -> Self {
+    pub async fn new(config: Config, metadata_options: MetadataOptions) -> Self {
         let (sql_sender, sql_receiver) = mpsc::channel(32);
         // 添加新的状态更新通道
         let (update_sender, update_receiver) = mpsc::channel(32);
         
-        let chains = match App::fetch_chains().await {
-            Ok(data) => data,
-            Err(_) => Vec::new()
-        };
+        let (chains, chains_source) = App::fetch_chains(&metadata_options).await;
+        let catalog = Catalog::build(&chains, config.table_sort.value);
+        let manuscripts = ManuscriptBrowser::new(config.workspace.value.clone(), catalog.clone());
+        let environments: Vec<DockerManager> = config.profiles.iter()
+            .map(|profile| DockerManager::new(profile, &config))
+            .collect();
+        let active_environment = environments.iter()
+            .position(|environment| environment.profile() == config.profile.value)
+            .unwrap_or(0);
 
         App {
             chains,
+            chains_source,
+            catalog,
             selected_chain_index: 0,
             selected_table_index: None,
             show_tables: false,
@@ -198,20 +351,48 @@ impl App {
             exit: false,
             current_tab: 0,  // Add this line
             example_data: None,  // Changed: Initialize as None
-            sql_input: String::new(),
+            samples: HashMap::new(),
+            sql_editor: TextEditor::default(),
+            completion: None,
             show_sql_window: false,
-            sql_cursor_position: 0,
+            keyboard_enhanced: false,
             sql_result: None,
             saved_sql: None,
             sql_executing: false,
             sql_error: None,
+            sql_stats: None,
             sql_columns: Vec::new(),
             sql_data: Vec::new(),
+            results_grid: GridState::default(),
+            example_grid: GridState::default(),
+            grid_focused: false,
+            finder: None,
+            highlighted_column: None,
             sql_sender: Some(sql_sender),
             sql_receiver: Some(sql_receiver),
             sql_timer: 0,  // Initialize timer
-            docker_manager: DockerManager::new(),
+            docker_manager: environments[active_environment].clone(),
+            environments,
+            active_environment,
+            show_environments: false,
+            environment_selected: active_environment,
             docker_status: None,
+            show_container: false,
+            container: ContainerStatus::Unknown,
+            container_message: None,
+            container_confirm: None,
+            container_busy: None,
+            container_expected: false,
+            container_died: false,
+            container_polling: false,
+            container_checked_at: None,
+            show_logs: false,
+            logs: LogView::default(),
+            log_task: None,
+            show_jobs: false,
+            jobs: JobsView::default(),
+            jobs_polling: false,
+            jobs_checked_at: None,
             docker_setup_in_progress: false,
             docker_setup_timer: 0,  // Initialize the timer
             setup_progress: 0.0,
@@ -219,43 +400,40 @@ impl App {
             state: AppState::default(),
             progress_columns: 0,
             progress1: 0.0,
+            pull_progress: None,
             progress_lines: RefCell::new(Vec::new()),
-            should_cancel_setup: false,  // Initialize the new field
+            setup_cancel: CancelHandle::default(),
             update_sender: Some(update_sender),
             update_receiver: Some(update_receiver),
             current_setup_step: None,
+            setup_step_complete: false,
+            setup_attempts: Vec::new(),
+            port_conflicts: Vec::new(),
+            manuscripts,
+            draft: None,
+            config,
+            show_config: false,
         }
     }
 
-    async fn fetch_chains() -> Result<Vec<Chain>, reqwest::Error> {
-        let url = "https://api.chainbase.com/api/v1/metadata/network_chains";
-
-        match reqwest::get(url).await?.json::<Response>().await {
-            Ok(response) => {
-                Ok(response.graphData.into_iter()
-                    .map(|graph_data| {
-                        let mut tables = HashMap::new();
-                        tables.insert("blocks".to_string(), graph_data.chain.dataDictionary.blocks);
-                        tables.insert("transactions".to_string(), graph_data.chain.dataDictionary.transactions);
-                        tables.insert("transactionLogs".to_string(), graph_data.chain.dataDictionary.transactionLogs);
-                        
-                        let time_ago = Self::calculate_time_diff(&graph_data.chain.lastUpdate);
-                        
-                        Chain {
-                            name: graph_data.chain.name,
-                            status: graph_data.chain.status,
-                            lastUpdate: graph_data.chain.lastUpdate,
-                            time_ago,
-                            dataDictionary: tables,
-                        }
-                    })
-                    .collect())
-            }
-            Err(e) => {
-                println!("Failed to parse JSON response: {:?}", e);
-                Err(e)
-            }
-        }
+    pub async fn fetch_chains(options: &MetadataOptions) -> (Vec<Chain>, ChainsSource) {
+        let (response, source) = metadata::load::<Response>(options).await;
+        let chains = response
+            .map(|response| response.graphData.into_iter()
+                .map(|graph_data| {
+                    let time_ago = Self::calculate_time_diff(&graph_data.chain.lastUpdate);
+
+                    Chain {
+                        name: graph_data.chain.name,
+                        status: graph_data.chain.status,
+                        lastUpdate: graph_data.chain.lastUpdate,
+                        time_ago,
+                        dataDictionary: graph_data.chain.dataDictionary,
+                    }
+                })
+                .collect())
+            .unwrap_or_default();
+        (chains, source)
     }
 
     fn calculate_time_diff(time_str: &str) -> String {
@@ -431,7 +609,7 @@ impl App {
             // Update timer if Docker setup is in progress
             if self.state == AppState::Started {
                 self.docker_setup_timer = self.docker_setup_timer.saturating_add(1);
-                self.update(terminal.size()?.width);
+                self.update();
             }
 
             // 等待事件,超时时间为100毫秒
@@ -446,11 +624,16 @@ impl App {
                 }
             }
 
+            self.poll_container();
+            self.poll_jobs();
+
             // Check for SQL execution results
             // 检查是否正在执行SQL查询
             if self.sql_executing {
+                self.sql_timer = self.sql_timer.saturating_add(1);
                 // 如果有接收器,尝试从通道中接收SQL执行结果
-                if let Some(receiver) = &mut self.sql_receiver {
+                // 一次取出所有已到达的分页
+                while let Some(receiver) = &mut self.sql_receiver {
                     // 非阻塞地尝试接收结果
                     match receiver.try_recv() {
                         // 成功接收到结果
@@ -459,23 +642,24 @@ impl App {
                             Ok(json) => self.handle_sql_response(json),
                             // SQL执行出错,记录错误信息并停止执行状态
                             Err(error) => {
-                                self.sql_error = Some(error);
+                                self.sql_error = Some(QueryError::transport(error));
                                 self.sql_executing = false;
                             }
                         },
                         // 通道为空,继续等待
-                        Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {},
+                        Err(tokio::sync::mpsc::error::TryRecvError::Empty) => break,
                         // 通道关闭,记录错误并停止执行状态
                         Err(_) => {
                             self.sql_executing = false;
-                            self.sql_error = Some("Channel closed".to_string());
+                            self.sql_error = Some(QueryError::transport("Channel closed".to_string()));
+                            break;
                         }
                     }
                 }
             }
 
             // 检查状态更新
-            if let Some(receiver) = &mut self.update_receiver {
+            while let Some(receiver) = &mut self.update_receiver {
                 match receiver.try_recv() {
                     Ok(update) => match update {
                         AppUpdate::DockerStatus(status) => {
@@ -483,10 +667,11 @@ impl App {
                         },
                         AppUpdate::SetupProgress(step, status) => {
                             self.current_setup_step = Some(step.clone());
+                            self.setup_step_complete = status == SetupStepStatus::Complete;
                             self.setup_state = match status {
                                 SetupStepStatus::Pending => SetupState::NotStarted,
-                                SetupStepStatus::InProgress => SetupState::InProgress,
-                                SetupStepStatus::Complete => SetupState::Complete,
+                                // 单个步骤完成不代表整个 setup 完成
+                                SetupStepStatus::InProgress | SetupStepStatus::Complete => SetupState::InProgress,
                                 SetupStepStatus::Failed => SetupState::Failed(format!("Step {} failed", step.as_str())),
                             };
                         },
@@ -497,6 +682,92 @@ impl App {
                             self.progress1 = 0.0;
                             self.docker_setup_in_progress = false;
                             self.current_setup_step = None;
+                            self.container_expected = true;
+                            self.container_died = false;
+                            self.container_checked_at = None;
+                        },
+                        AppUpdate::SetupCancelled(message) => {
+                            self.docker_status = Some(message);
+                            self.state = AppState::Running;
+                            self.setup_state = SetupState::Cancelled;
+                            self.docker_setup_timer = 0;
+                            self.progress1 = 0.0;
+                            self.docker_setup_in_progress = false;
+                        },
+                        AppUpdate::SetupAttemptFailed(step, attempt) => {
+                            self.setup_attempts.push((step, attempt));
+                        },
+                        AppUpdate::PortsInUse(conflicts) => self.port_conflicts = conflicts,
+                        AppUpdate::PortsChanged(ports) => {
+                            self.port_conflicts.clear();
+                            self.docker_status = Some(format!("Using host ports {}", ports));
+                            // 之前因为端口不对而失败的示例数据需要重新拉取
+                            self.samples.retain(|_, sample| matches!(sample, SampleState::Loaded(_)));
+                        },
+                        AppUpdate::ContainerStatus(name, result) => self.handle_container_status(&name, result),
+                        // 切换环境后忽略旧容器的日志
+                        AppUpdate::LogLines(name, lines) if name == self.docker_manager.container_name() => self.logs.push(lines),
+                        AppUpdate::LogsEnded(name, error) if name == self.docker_manager.container_name() => {
+                            self.logs.following = false;
+                            self.logs.message = Some(error.unwrap_or_else(|| "Log stream ended (f: Follow again)".to_string()));
+                        },
+                        AppUpdate::LogLines(..) | AppUpdate::LogsEnded(..) => {},
+                        AppUpdate::ContainerActionDone(action, result) => {
+                            self.container_busy = None;
+                            self.container_checked_at = None;
+                            match result {
+                                Ok(()) => {
+                                    self.container_expected = !matches!(action, ContainerAction::Stop | ContainerAction::Remove);
+                                    self.container_died = false;
+                                    self.container_message = Some(format!("Container {}", action.past_tense()));
+                                }
+                                Err(e) => self.container_message = Some(e),
+                            }
+                        },
+                        AppUpdate::PullProgress(progress) => {
+                            self.pull_progress = Some(progress);
+                        },
+                        AppUpdate::JobsLoaded(endpoint, result) => {
+                            self.jobs_polling = false;
+                            // 切换环境后忽略旧节点的结果
+                            if endpoint == self.docker_manager.job_endpoint() {
+                                self.jobs.set_jobs(result);
+                            }
+                        },
+                        AppUpdate::JobSubmitted(name, result) => {
+                            self.manuscripts.message = Some(match result {
+                                Ok(id) => format!("Submitted {} as job {} (j: Jobs)", name, id),
+                                Err(e) => format!("Failed to submit {}: {}", name, e),
+                            });
+                            self.jobs_checked_at = None;
+                        },
+                        AppUpdate::JobCancelled(id, result) => {
+                            self.jobs.message = Some(match result {
+                                Ok(()) => format!("Cancellation requested for job {}", id),
+                                Err(e) => format!("Failed to cancel job {}: {}", id, e),
+                            });
+                            self.jobs_checked_at = None;
+                        },
+                        AppUpdate::JobMetricsLoaded(id, result) => {
+                            self.jobs_polling = false;
+                            if let Some(dashboard) = self.jobs.dashboard.as_mut().filter(|dashboard| dashboard.job_id == id) {
+                                dashboard.update(result);
+                            }
+                        },
+                        AppUpdate::JobExceptionsLoaded(id, result) => {
+                            if self.jobs.exceptions.as_ref().is_some_and(|(viewing, _)| *viewing == id) {
+                                self.jobs.exceptions = Some((id, result));
+                            }
+                        },
+                        AppUpdate::SampleLoaded(table_id, result) => {
+                            let state = match result {
+                                Ok(data) => SampleState::Loaded(data),
+                                Err(e) => SampleState::Failed(e),
+                            };
+                            self.samples.insert(table_id.clone(), state);
+                            if self.selected_table().map(|table| &table.id) == Some(&table_id) {
+                                self.update_example_data();
+                            }
                         },
                         AppUpdate::SetupFailed(error, step) => {
                             self.docker_status = Some(format!("Error: {}", error));
@@ -508,9 +779,10 @@ impl App {
                             self.current_setup_step = Some(step);
                         }
                     },
-                    Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {},
+                    Err(tokio::sync::mpsc::error::TryRecvError::Empty) => break,
                     Err(_) => {
                         self.docker_status = Some("Channel closed".to_string());
+                        break;
                     }
                 }
             }
@@ -518,159 +790,550 @@ impl App {
         Ok(())
     }
 
-    fn update(&mut self, terminal_width: u16) {
-        if self.should_cancel_setup {
+    fn update(&mut self) {
+        if self.setup_cancel.is_cancelled() {
             // Reset everything if cancellation is requested
             self.progress1 = 0.0;
             self.docker_setup_timer = 0;
             return;
         }
 
-        let total_duration = 1200;
-        self.progress1 = (self.docker_setup_timer as f64 * 100.0 / total_duration as f64).min(100.0);
-        
-        if self.progress1 >= 100.0 {
-            self.state = AppState::Running;
-            self.docker_setup_timer = 0;
-        }
+        // 拉取镜像占整体进度的 80%，按实际下载和解压进度计算
+        let pulled = self.pull_progress.as_ref().map(PullProgress::fraction).unwrap_or(0.0);
+        self.progress1 = match self.current_setup_step {
+            None | Some(SetupStep::CheckingDocker) => 0.0,
+            Some(SetupStep::PullingImage) => 5.0 + pulled * 80.0,
+            Some(SetupStep::StartingContainer) => 85.0,
+            Some(SetupStep::ConfiguringNetwork) => 90.0,
+            Some(SetupStep::VerifyingSetup) => 95.0,
+        };
     }
 
-    pub fn render_gauge1(&self, area: Rect, buf: &mut Buffer) {
-        let title = title_block("Gauge with ratio and custom label");
-        let label = Span::styled(
-            format!("{:.1}/100", self.progress1),
-            Style::new().italic().bold().fg(CUSTOM_LABEL_COLOR),
-        );
-        Gauge::default()
-            .block(title)
-            .gauge_style(GAUGE2_COLOR)
-            .ratio(self.progress1 / 100.0)
-            .label(label)
-            .render(area, buf);
+    pub fn progress_label(&self) -> String {
+        match (&self.current_setup_step, &self.pull_progress) {
+            (Some(SetupStep::PullingImage), Some(progress)) => {
+                let mut label = format!(
+                    "{:.1}/100 | {} layers, {} extracting, {} done",
+                    self.progress1,
+                    progress.layers.len(),
+                    progress.extracting(),
+                    progress.complete(),
+                );
+                if progress.total_bytes() > 0 {
+                    label.push_str(&format!(
+                        " | {} / {}",
+                        pull::format_bytes(progress.downloaded_bytes()),
+                        pull::format_bytes(progress.total_bytes()),
+                    ));
+                }
+                if let Some(eta) = progress.eta() {
+                    label.push_str(&format!(" | ETA {}:{:02}", eta.as_secs() / 60, eta.as_secs() % 60));
+                }
+                label
+            }
+            _ => format!("{:.1}/100", self.progress1()),
+        }
     }
 
     pub fn update_example_data(&mut self) {
-        if let Some(selected_chain) = self.chains.get(self.selected_chain_index) {
-            // Check if chain is offline
-            if selected_chain.status == "Offline" {
-                self.example_data = None;
-                return;
-            }
-
-            if let Some(table_index) = self.selected_table_index {
-                let table_name = selected_chain.dataDictionary
-                    .keys()
-                    .nth(table_index)
-                    .map(|s| s.as_str());
-
-                self.example_data = match table_name {
-                    Some("blocks") => Some(Self::mock_blocks_data()),
-                    Some("transactions") => Some(Self::mock_transactions_data()),
-                    Some("transactionLogs") => Some(Self::mock_transaction_logs_data()),
-                    _ => None,
-                };
+        self.example_grid.reset();
+        self.highlighted_column = None;
+        self.example_data = None;
+
+        // Check if chain is offline
+        if self.chains.get(self.selected_chain_index).is_none_or(|chain| chain.status == "Offline") {
+            return;
+        }
+        let Some(table) = self.selected_table() else {
+            return;
+        };
+
+        // 演示模式只使用内置的 mock 数据
+        if self.config.demo.value {
+            self.example_data = match table.name.as_str() {
+                "blocks" => Some(Self::mock_blocks_data()),
+                "transactions" => Some(Self::mock_transactions_data()),
+                "transactionLogs" => Some(Self::mock_transaction_logs_data()),
+                _ => None,
+            };
+            return;
+        }
+
+        let table_id = table.id.clone();
+        let order_by = table.recency_column().map(str::to_string);
+        match self.samples.get(&table_id) {
+            Some(SampleState::Loaded(data)) => self.example_data = Some(data.clone()),
+            Some(_) => {}
+            None => self.fetch_sample(table_id, order_by),
+        }
+    }
+
+    // 从本地 debug 节点拉取最新的若干行作为示例数据；表没有区块号或时间字段时行的顺序不确定
+    fn fetch_sample(&mut self, table_id: TableId, order_by: Option<String>) {
+        let Some(sender) = self.update_sender.clone() else {
+            return;
+        };
+        self.samples.insert(table_id.clone(), SampleState::Loading);
+
+        let client = SqlClient::new(&self.docker_manager.sql_endpoint());
+        tokio::spawn(async move {
+            let sql = match order_by {
+                Some(column) => format!("SELECT * FROM {} ORDER BY {} DESC LIMIT {}", table_id, column, SAMPLE_ROWS),
+                None => format!("SELECT * FROM {} LIMIT {}", table_id, SAMPLE_ROWS),
+            };
+            let result = client.query(&sql)
+                .await
+                .map(|(columns, data)| ExampleData { columns, data })
+                .map_err(|e| e.to_string());
+            let _ = sender.send(AppUpdate::SampleLoaded(table_id, result)).await;
+        });
+    }
+
+    // 当前选中表的示例数据状态
+    pub fn selected_sample(&self) -> Option<&SampleState> {
+        self.samples.get(&self.selected_table()?.id)
+    }
+
+    // 面板打开或容器应当在运行时，定期检查容器状态
+    fn poll_container(&mut self) {
+        if self.container_polling || !(self.show_container || self.container_expected) {
+            return;
+        }
+        if self.container_checked_at.is_some_and(|checked_at| checked_at.elapsed() < CONTAINER_POLL_INTERVAL) {
+            return;
+        }
+        let Some(sender) = self.update_sender.clone() else {
+            return;
+        };
+        self.container_polling = true;
+        self.container_checked_at = Some(Instant::now());
+
+        let docker_manager = self.docker_manager.clone();
+        tokio::spawn(async move {
+            let result = docker_manager.container_info().await;
+            let name = docker_manager.container_name().to_string();
+            let _ = sender.send(AppUpdate::ContainerStatus(name, result)).await;
+        });
+    }
+
+    fn handle_container_status(&mut self, name: &str, result: Result<Option<ContainerInfo>, String>) {
+        self.container_polling = false;
+        if name != self.docker_manager.container_name() {
+            return;
+        }
+        self.container = match result {
+            Ok(Some(info)) => ContainerStatus::Found(info),
+            Ok(None) => ContainerStatus::Missing,
+            Err(e) => ContainerStatus::Failed(e),
+        };
+
+        // setup 之后容器退出或被删除：提示并打开面板让用户重新启动
+        let running = matches!(&self.container, ContainerStatus::Found(info) if info.running);
+        let gone = matches!(&self.container, ContainerStatus::Missing)
+            || matches!(&self.container, ContainerStatus::Found(info) if !info.running);
+        if self.container_expected && gone && self.container_busy.is_none() && !self.container_died {
+            self.container_died = true;
+            self.show_container = true;
+            self.docker_status = Some(format!("Container {} is no longer running", self.docker_manager.container_name()));
+        } else if running {
+            self.container_died = false;
+        }
+    }
+
+    fn handle_container_key_event(&mut self, key_event: KeyEvent) {
+        if let Some(action) = self.container_confirm {
+            if key_event.code == KeyCode::Char('y') {
+                self.run_container_action(action);
+            }
+            self.container_confirm = None;
+            return;
+        }
+
+        let exists = matches!(self.container, ContainerStatus::Found(_));
+        let action = match key_event.code {
+            KeyCode::Esc | KeyCode::Char('d') => {
+                self.show_container = false;
+                None
+            }
+            KeyCode::Char('q') => {
+                self.exit = true;
+                None
+            }
+            KeyCode::Char('t') if exists => Some(ContainerAction::Stop),
+            KeyCode::Char('r') if exists => Some(ContainerAction::Restart),
+            KeyCode::Char('x') if exists => Some(ContainerAction::Remove),
+            KeyCode::Char('n') => Some(ContainerAction::Recreate),
+            _ => None,
+        };
+        if self.container_busy.is_none() {
+            self.container_confirm = action;
+        }
+    }
+
+    fn run_container_action(&mut self, action: ContainerAction) {
+        let Some(sender) = self.update_sender.clone() else {
+            return;
+        };
+        self.container_busy = Some(action);
+        self.container_message = None;
+
+        let docker_manager = self.docker_manager.clone();
+        tokio::spawn(async move {
+            let result = docker_manager.apply(action).await;
+            let _ = sender.send(AppUpdate::ContainerActionDone(action, result)).await;
+        });
+    }
+
+    // 在后台跟随容器日志，重新连接时从最近的若干行开始
+    fn follow_logs(&mut self) {
+        let Some(sender) = self.update_sender.clone() else {
+            return;
+        };
+        self.logs.lines.clear();
+        self.logs.following = true;
+        self.logs.message = None;
+
+        let docker_manager = self.docker_manager.clone();
+        let task = tokio::spawn(async move {
+            docker_manager.follow_logs(sender).await;
+        });
+        self.log_task = Some(task.abort_handle());
+    }
+
+    fn handle_logs_key_event(&mut self, key_event: KeyEvent) {
+        let logs = &mut self.logs;
+        if logs.searching {
+            match key_event.code {
+                KeyCode::Esc => {
+                    logs.searching = false;
+                    logs.search.clear();
+                }
+                KeyCode::Enter => {
+                    logs.searching = false;
+                    logs.jump_to_match(true);
+                }
+                KeyCode::Backspace => {
+                    logs.search.pop();
+                }
+                KeyCode::Char(c) => logs.search.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        let page = logs.height.get().max(2) / 2;
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('l') => self.show_logs = false,
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Char('p') | KeyCode::Char(' ') => logs.toggle_pause(),
+            KeyCode::Up => logs.scroll_up(1),
+            KeyCode::Down => logs.scroll_down(1),
+            KeyCode::PageUp => logs.scroll_up(page),
+            KeyCode::PageDown => logs.scroll_down(page),
+            KeyCode::Home => logs.top(),
+            KeyCode::End => logs.bottom(),
+            KeyCode::Char('/') => {
+                logs.searching = true;
+                logs.search.clear();
+            }
+            KeyCode::Char('n') => logs.jump_to_match(true),
+            KeyCode::Char('N') => logs.jump_to_match(false),
+            KeyCode::Char('w') => {
+                logs.message = Some(match logs.save(self.docker_manager.container_name()) {
+                    Ok(path) => format!("Saved {} lines to {}", logs.lines.len(), path.display()),
+                    Err(e) => format!("Failed to save logs: {}", e),
+                });
+            }
+            KeyCode::Char('f') if !logs.following => self.follow_logs(),
+            _ => {}
+        }
+    }
+
+    // 面板打开时定期刷新作业列表，打开指标页时改为刷新该作业的指标
+    fn poll_jobs(&mut self) {
+        if self.jobs_polling || !self.show_jobs {
+            return;
+        }
+        if self.jobs_checked_at.is_some_and(|checked_at| checked_at.elapsed() < JOBS_POLL_INTERVAL) {
+            return;
+        }
+        let Some(sender) = self.update_sender.clone() else {
+            return;
+        };
+        self.jobs_polling = true;
+        self.jobs_checked_at = Some(Instant::now());
+
+        let endpoint = self.docker_manager.job_endpoint();
+        if let Some(id) = self.jobs.dashboard.as_ref().map(|dashboard| dashboard.job_id.clone()) {
+            tokio::spawn(async move {
+                let result = JobClient::new(&endpoint).metrics(&id).await;
+                let _ = sender.send(AppUpdate::JobMetricsLoaded(id, result)).await;
+            });
+            return;
+        }
+        tokio::spawn(async move {
+            let result = JobClient::new(&endpoint).jobs().await;
+            let _ = sender.send(AppUpdate::JobsLoaded(endpoint, result)).await;
+        });
+    }
+
+    fn handle_jobs_key_event(&mut self, key_event: KeyEvent) {
+        let jobs = &mut self.jobs;
+        if jobs.confirm_cancel {
+            jobs.confirm_cancel = false;
+            if key_event.code == KeyCode::Char('y') {
+                if let Some(id) = jobs.selected_job().map(|job| job.id.clone()) {
+                    self.cancel_job(id);
+                }
+            }
+            return;
+        }
+
+        // 指标页
+        if jobs.dashboard.is_some() {
+            match key_event.code {
+                KeyCode::Esc | KeyCode::Char('m') => {
+                    jobs.dashboard = None;
+                    self.jobs_checked_at = None;
+                }
+                KeyCode::Char('q') => self.exit = true,
+                _ => {}
+            }
+            return;
+        }
+
+        // 异常详情
+        if jobs.exceptions.is_some() {
+            let page = jobs.height.get().max(1);
+            match key_event.code {
+                KeyCode::Esc | KeyCode::Enter => jobs.exceptions = None,
+                KeyCode::Char('q') => self.exit = true,
+                KeyCode::Up => jobs.scroll_up(1),
+                KeyCode::Down => jobs.scroll_down(1),
+                KeyCode::PageUp => jobs.scroll_up(page),
+                KeyCode::PageDown => jobs.scroll_down(page),
+                _ => {}
+            }
+            return;
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('j') => self.show_jobs = false,
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Up => jobs.up(),
+            KeyCode::Down => jobs.down(),
+            KeyCode::Char('g') => {
+                jobs.message = None;
+                self.jobs_checked_at = None;
+            }
+            KeyCode::Char('c') => match jobs.selected_job() {
+                Some(job) if job.can_cancel() => jobs.confirm_cancel = true,
+                Some(job) => jobs.message = Some(format!("Job {} is already {}", job.id, job.state.to_lowercase())),
+                None => {}
+            },
+            KeyCode::Enter => {
+                if let Some(id) = jobs.selected_job().map(|job| job.id.clone()) {
+                    self.load_exceptions(id);
+                }
+            }
+            KeyCode::Char('m') => {
+                if let Some(job) = jobs.selected_job() {
+                    jobs.dashboard = Some(JobDashboard::new(job));
+                    self.jobs_checked_at = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn cancel_job(&mut self, id: String) {
+        let Some(sender) = self.update_sender.clone() else {
+            return;
+        };
+        self.jobs.message = Some(format!("Cancelling job {}...", id));
+        let client = JobClient::new(&self.docker_manager.job_endpoint());
+        tokio::spawn(async move {
+            let result = client.cancel(&id).await;
+            let _ = sender.send(AppUpdate::JobCancelled(id, result)).await;
+        });
+    }
+
+    fn load_exceptions(&mut self, id: String) {
+        let Some(sender) = self.update_sender.clone() else {
+            return;
+        };
+        self.jobs.scroll = 0;
+        self.jobs.exceptions = Some((id.clone(), Err("Loading...".to_string())));
+        let client = JobClient::new(&self.docker_manager.job_endpoint());
+        tokio::spawn(async move {
+            let result = client.exceptions(&id).await;
+            let _ = sender.send(AppUpdate::JobExceptionsLoaded(id, result)).await;
+        });
+    }
+
+    // 把 MANUSCRIPTS 页选中的 manuscript 提交到当前环境的 job manager
+    fn submit_manuscript(&mut self) {
+        let Some(file) = self.manuscripts.selected_file() else {
+            return;
+        };
+        let errors = file.errors();
+        if errors > 0 {
+            self.manuscripts.message = Some(format!("Fix {} error(s) in {} before submitting", errors, file.name()));
+            return;
+        }
+        let Some(sender) = self.update_sender.clone() else {
+            return;
+        };
+        let (name, content) = (file.name(), file.content.clone());
+        let parallelism = file.manuscript.as_ref().ok().and_then(|manuscript| manuscript.parallelism);
+        let client = JobClient::new(&self.docker_manager.job_endpoint());
+        self.manuscripts.message = Some(format!("Submitting {} to {}...", name, client.base_url()));
+        tokio::spawn(async move {
+            let result = client.submit(&content, parallelism).await;
+            let _ = sender.send(AppUpdate::JobSubmitted(name, result)).await;
+        });
+    }
+
+    fn handle_environments_key_event(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('p') => self.show_environments = false,
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Up => self.environment_selected = self.environment_selected.saturating_sub(1),
+            KeyCode::Down => {
+                if self.environment_selected + 1 < self.environments.len() {
+                    self.environment_selected += 1;
+                }
             }
+            KeyCode::Enter => {
+                self.switch_environment(self.environment_selected);
+                self.show_environments = false;
+            }
+            _ => {}
         }
     }
 
+    // 切换查询、日志和容器面板使用的调试环境
+    fn switch_environment(&mut self, index: usize) {
+        if index == self.active_environment || index >= self.environments.len() {
+            return;
+        }
+        if self.docker_setup_in_progress {
+            self.docker_status = Some("Wait for the current setup to finish before switching environments".to_string());
+            return;
+        }
+
+        self.active_environment = index;
+        self.docker_manager = self.environments[index].clone();
+
+        self.container = ContainerStatus::Unknown;
+        self.container_message = None;
+        self.container_confirm = None;
+        self.container_expected = false;
+        self.container_died = false;
+        self.container_checked_at = None;
+        if let Some(task) = self.log_task.take() {
+            task.abort();
+        }
+        self.logs = LogView::default();
+        self.jobs = JobsView::default();
+        self.jobs_checked_at = None;
+        self.setup_state = SetupState::NotStarted;
+        self.current_setup_step = None;
+        self.setup_attempts.clear();
+        self.port_conflicts.clear();
+        self.pull_progress = None;
+
+        // 示例数据来自之前环境的节点
+        self.samples.clear();
+        self.update_example_data();
+        self.docker_status = Some(format!(
+            "Switched to environment {} ({})",
+            self.docker_manager.profile(),
+            self.docker_manager.sql_endpoint()
+        ));
+    }
+
     fn handle_key_event(&mut self, key_event: KeyEvent, visible_height: usize) {
-        if self.show_sql_window {
+        if self.draft.is_some() {
+            self.handle_draft_key_event(key_event);
+        } else if self.show_environments {
+            self.handle_environments_key_event(key_event);
+        } else if self.show_logs {
+            self.handle_logs_key_event(key_event);
+        } else if self.show_jobs {
+            self.handle_jobs_key_event(key_event);
+        } else if self.show_container {
+            self.handle_container_key_event(key_event);
+        } else if self.show_sql_window && self.completion.is_some() && self.handle_completion_key_event(key_event) {
+            // 补全弹窗自己处理的按键
+        } else if self.show_sql_window {
             match key_event.code {
                 KeyCode::Esc => {
                     // Save the SQL when closing the window
-                    if !self.sql_input.trim().is_empty() {
-                        self.saved_sql = Some(self.sql_input.clone());
+                    if !self.sql_editor.text.trim().is_empty() {
+                        self.saved_sql = Some(self.sql_editor.text.clone());
                     }
                     // Reset SQL window state
                     self.show_sql_window = false;
                     self.sql_result = None;
                     // Don't clear the selected table index anymore
                 }
-                KeyCode::Enter => {
-                    // Execute SQL when Ctrl+Enter is pressed
-                    if key_event.modifiers.contains(event::KeyModifiers::CONTROL) {
-                        // tokio::spawn({
-                        //     let mut app = self.clone();
-                        //     async move {
-                        //         app.execute_sql().await;
-                        //         app
-                        //     }
-                        // });
-                    } else {
-                        // Insert newline at cursor position
-                        self.sql_input.insert(self.sql_cursor_position, '\n');
-                        self.sql_cursor_position += 1;
-                    }
+                // Execute SQL when Ctrl+Enter is pressed
+                KeyCode::Enter if key_event.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                    self.execute_sql();
                 }
-                KeyCode::Char(c) => {
-                    self.sql_input.insert(self.sql_cursor_position, c);
-                    self.sql_cursor_position += 1;
+                // 不支持 kitty 键盘协议的终端收不到 Ctrl+Enter
+                KeyCode::F(5) => self.execute_sql(),
+                KeyCode::Char('r') if key_event.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                    self.execute_sql();
                 }
-                KeyCode::Backspace => {
-                    if self.sql_cursor_position > 0 {
-                        self.sql_input.remove(self.sql_cursor_position - 1);
-                        self.sql_cursor_position -= 1;
-                    }
+                // Tab 在行首仍然是缩进
+                KeyCode::Tab if !self.at_line_start() => self.complete(true),
+                KeyCode::Char(' ') if key_event.modifiers.contains(event::KeyModifiers::CONTROL) => self.complete(false),
+                _ => {
+                    self.sql_editor.handle_key(key_event);
                 }
-                KeyCode::Left => {
-                    if self.sql_cursor_position > 0 {
-                        self.sql_cursor_position -= 1;
-                    }
+            }
+        } else if self.finder.is_some() {
+            self.handle_finder_key_event(key_event, visible_height);
+        } else if self.current_tab == 1 && self.handle_manuscripts_key_event(key_event) {
+            // MANUSCRIPTS 页自己处理的按键
+        } else if self.grid_focused {
+            self.handle_grid_key_event(key_event, visible_height);
+        } else {
+            match key_event.code {
+                KeyCode::Char('q') => self.exit = true,
+                KeyCode::Char('/') => self.finder = Some(Finder::new(&self.chains, &self.catalog)),
+                KeyCode::Char('c') => self.show_config = !self.show_config,
+                KeyCode::Char('d') => {
+                    self.show_container = true;
+                    self.container_checked_at = None;
                 }
-                KeyCode::Right => {
-                    if self.sql_cursor_position < self.sql_input.len() {
-                        self.sql_cursor_position += 1;
-                    }
+                KeyCode::Char('p') => {
+                    self.show_environments = true;
+                    self.environment_selected = self.active_environment;
                 }
-                KeyCode::Up => {
-                    // Find the previous newline before cursor
-                    let before_cursor = &self.sql_input[..self.sql_cursor_position];
-                    if let Some(current_line_start) = before_cursor.rfind('\n') {
-                        // Get the previous line's start
-                        if let Some(prev_line_start) = before_cursor[..current_line_start].rfind('\n') {
-                            let current_col = self.sql_cursor_position - current_line_start - 1;
-                            let prev_line_length = current_line_start - prev_line_start - 1;
-                            let new_col = current_col.min(prev_line_length);
-                            self.sql_cursor_position = prev_line_start + 1 + new_col;
-                        } else {
-                            // Move to the first line
-                            let current_col = self.sql_cursor_position - current_line_start - 1;
-                            let first_line_length = current_line_start;
-                            let new_col = current_col.min(first_line_length);
-                            self.sql_cursor_position = new_col;
-                        }
+                KeyCode::Char('l') => {
+                    self.show_logs = true;
+                    if !self.logs.following {
+                        self.follow_logs();
                     }
                 }
-                KeyCode::Down => {
-                    // Find the next newline after cursor
-                    if let Some(current_line_end) = self.sql_input[self.sql_cursor_position..].find('\n') {
-                        let current_line_end = current_line_end + self.sql_cursor_position;
-                        // Find the current line start to calculate column position
-                        let before_cursor = &self.sql_input[..self.sql_cursor_position];
-                        let current_line_start = before_cursor.rfind('\n')
-                            .map(|pos| pos + 1)
-                            .unwrap_or(0);
-                        let current_col = self.sql_cursor_position - current_line_start;
-
-                        // Find the next line's end
-                        if let Some(next_line_end) = self.sql_input[current_line_end + 1..].find('\n') {
-                            let next_line_end = next_line_end + current_line_end + 1;
-                            let next_line_length = next_line_end - (current_line_end + 1);
-                            let new_col = current_col.min(next_line_length);
-                            self.sql_cursor_position = current_line_end + 1 + new_col;
-                        } else {
-                            // Move to the last line
-                            let next_line_length = self.sql_input.len() - (current_line_end + 1);
-                            let new_col = current_col.min(next_line_length);
-                            self.sql_cursor_position = current_line_end + 1 + new_col;
-                        }
+                KeyCode::Char('j') => {
+                    self.show_jobs = true;
+                    self.jobs_checked_at = None;
+                }
+                KeyCode::Char('s') if self.show_tables && !self.config.demo.value => {
+                    // 重新拉取当前表的示例数据
+                    if let Some(table_id) = self.selected_table().map(|table| table.id.clone()) {
+                        self.samples.remove(&table_id);
+                        self.update_example_data();
                     }
                 }
-                _ => {}
-            }
-        } else {
-            match key_event.code {
-                KeyCode::Char('q') => self.exit = true,
+                KeyCode::Char('f') => {
+                    // 聚焦到当前可见的结果表格
+                    self.grid_focused = self.active_grid_size().is_some_and(|(_, rows)| rows > 0);
+                }
                 KeyCode::Up => {
                     if !self.show_tables {
                         if self.selected_chain_index > 0 {
@@ -691,7 +1354,7 @@ impl App {
                 }
                 KeyCode::Down => {
                     if !self.show_tables {
-                        if self.selected_chain_index < self.chains.len() - 1 {
+                        if self.selected_chain_index + 1 < self.chains.len() {
                             self.selected_chain_index += 1;
                             // 使用实际可见高度计算滚动位置
                             if self.selected_chain_index >= self.scroll_offset + visible_height {
@@ -700,8 +1363,8 @@ impl App {
                         }
                     } else {
                         if let Some(index) = self.selected_table_index {
-                            let tables_len = self.chains[self.selected_chain_index].dataDictionary.len();
-                            if index < tables_len - 1 {
+                            let tables_len = self.catalog.tables(self.selected_chain_index).len();
+                            if index + 1 < tables_len {
                                 self.selected_table_index = Some(index + 1);
                                 self.update_example_data();
                             }
@@ -716,18 +1379,16 @@ impl App {
                     } else {
                         // When table is selected, show SQL window
                         self.show_sql_window = true;
-                        self.sql_input = self.generate_initial_sql();
-                        self.sql_cursor_position = self.sql_input.len();
+                        self.sql_editor = TextEditor::new(self.generate_initial_sql());
                     }
                 }
+                // setup 进行中时 Esc 先取消 setup，配置视图在下一次 Esc 时关闭
+                KeyCode::Esc if self.show_config && !self.setup_cancellable() => self.show_config = false,
                 KeyCode::Esc => {
-                    if self.state == AppState::Started {
-                        // Cancel the setup process
-                        self.should_cancel_setup = true;
-                        self.state = AppState::Running;
-                        self.docker_setup_timer = 0;
-                        self.progress1 = 0.0;
-                        self.docker_setup_in_progress = false;
+                    if self.setup_cancellable() {
+                        // 通知后台任务取消，等它清理完成后会发送 SetupCancelled
+                        self.setup_cancel.cancel();
+                        self.docker_status = Some("Cancelling setup...".to_string());
                     }
                     if self.show_tables && self.saved_sql.is_some() {
                         // Clear saved SQL and return to table view
@@ -759,7 +1420,7 @@ impl App {
                         if new_index < self.chains.len() {
                             self.selected_chain_index = new_index;
                         } else {
-                            self.selected_chain_index = self.chains.len() - 1;
+                            self.selected_chain_index = self.chains.len().saturating_sub(1);
                         }
                         // 更新滚动位置
                         if self.selected_chain_index >= self.scroll_offset + visible_height {
@@ -768,89 +1429,429 @@ impl App {
                     }
                 }
                 KeyCode::Tab => {
-                    self.current_tab = (self.current_tab + 1) % 2;
+                    self.select_tab((self.current_tab + 1) % 2);
                 }
                 KeyCode::Char('1') => {
-                    self.current_tab = 0;
+                    self.select_tab(0);
                 }
                 KeyCode::Char('2') => {
-                    self.current_tab = 1;
+                    self.select_tab(1);
                 }
                 KeyCode::Char('e') => {
                     // 如果保存的 SQL 并且正在显示表格，允许重新编辑
                     if self.show_tables && self.saved_sql.is_some() {
                         self.show_sql_window = true;
-                        self.sql_input = self.saved_sql.clone().unwrap_or_default();
-                        self.sql_cursor_position = self.sql_input.len();
+                        self.sql_editor = TextEditor::new(self.saved_sql.clone().unwrap_or_default());
                     }
                 }
-                KeyCode::Char('r') => {
-                    self.state = AppState::Started;
-                    self.should_cancel_setup = false;  // Reset cancel flag
-                    if !self.docker_setup_in_progress {
-                        tokio::spawn({
-                            let mut app = self.clone();
-                            async move {
-                                app.setup_docker().await;
-                            }
-                        });
+                KeyCode::Char('m') if self.show_tables => {
+                    // 用保存的 SQL 生成 manuscript，没有时用默认的查询
+                    if let Some(table) = self.selected_table() {
+                        let sql = self.saved_sql.clone().unwrap_or_else(|| self.generate_initial_sql());
+                        self.draft = Some(ManuscriptDraft::new(&table.id.chain, &table.id.table, &sql));
                     }
+                }
+                KeyCode::Char('r') => self.start_setup(),
+                KeyCode::Char('a') if !self.port_conflicts.is_empty() => {
+                    // 端口被占用时换用空闲端口重新 setup
+                    self.docker_manager.set_auto_ports(true);
+                    self.environments[self.active_environment].set_auto_ports(true);
+                    self.start_setup();
                 },
                 _ => {}
             }
         }
     }
 
+    fn select_tab(&mut self, tab: usize) {
+        self.current_tab = tab;
+        // 每次进入 MANUSCRIPTS 页都重新扫描，文件可能在外部被修改
+        if tab == 1 {
+            self.manuscripts.scan();
+        }
+    }
+
+    // 返回 false 时交给主界面的通用按键处理
+    fn handle_manuscripts_key_event(&mut self, key_event: KeyEvent) -> bool {
+        let browser = &mut self.manuscripts;
+        if let Some(prompt) = &mut browser.prompt {
+            match key_event.code {
+                KeyCode::Esc => browser.prompt = None,
+                KeyCode::Backspace => {
+                    prompt.input.pop();
+                }
+                KeyCode::Char(c) => prompt.input.push(c),
+                KeyCode::Enter => {
+                    let Some(prompt) = browser.prompt.take() else {
+                        return true;
+                    };
+                    let name = prompt.input.trim();
+                    let result = match prompt.action {
+                        PromptAction::Create => {
+                            let content = self.new_manuscript_template(name);
+                            self.manuscripts.create(name, &content)
+                        }
+                        PromptAction::Duplicate => self.manuscripts.duplicate(name),
+                    };
+                    self.manuscripts.message = Some(match result {
+                        Ok(path) => format!("Created {}", path.display()),
+                        Err(e) => e,
+                    });
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        if browser.confirm_delete {
+            browser.confirm_delete = false;
+            if key_event.code == KeyCode::Char('y') {
+                browser.message = Some(match browser.delete() {
+                    Ok(path) => format!("Deleted {}", path.display()),
+                    Err(e) => e,
+                });
+            }
+            return true;
+        }
+
+        let page = browser.height.get().max(1);
+        match key_event.code {
+            KeyCode::Up if browser.viewing => browser.scroll_up(1),
+            KeyCode::Down if browser.viewing => browser.scroll_down(1),
+            KeyCode::PageUp if browser.viewing => browser.scroll_up(page),
+            KeyCode::PageDown if browser.viewing => browser.scroll_down(page),
+            KeyCode::Esc if browser.viewing => browser.viewing = false,
+            KeyCode::Up => browser.up(),
+            KeyCode::Down => browser.down(),
+            KeyCode::Enter => browser.viewing = browser.selected_file().is_some(),
+            KeyCode::Char('n') => {
+                browser.message = None;
+                browser.prompt = Some(NamePrompt { action: PromptAction::Create, input: String::new() });
+            }
+            KeyCode::Char('D') if browser.selected_file().is_some() => {
+                let input = browser.selected_file().map(|file| format!("{}_copy", file.name())).unwrap_or_default();
+                browser.message = None;
+                browser.prompt = Some(NamePrompt { action: PromptAction::Duplicate, input });
+            }
+            KeyCode::Char('x') if browser.selected_file().is_some() => browser.confirm_delete = true,
+            KeyCode::Char('s') if browser.selected_file().is_some() => self.submit_manuscript(),
+            KeyCode::Char('g') => {
+                browser.scan();
+                browser.message = Some(format!("Found {} manuscripts", browser.files.len()));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn handle_draft_key_event(&mut self, key_event: KeyEvent) {
+        let Some(draft) = &mut self.draft else {
+            return;
+        };
+        let Some(editor) = &mut draft.editor else {
+            // 选项步骤：输入名称，Tab/←/→ 切换 sink
+            match key_event.code {
+                KeyCode::Esc => self.draft = None,
+                KeyCode::Tab | KeyCode::Left | KeyCode::Right => draft.sink = draft.sink.next(),
+                KeyCode::Backspace => {
+                    draft.name.pop();
+                }
+                KeyCode::Char(c) => draft.name.push(c),
+                KeyCode::Enter => {
+                    if let Err(e) = draft.generate() {
+                        draft.message = Some(e);
+                    }
+                }
+                _ => {}
+            }
+            return;
+        };
+
+        match key_event.code {
+            // 回到选项步骤，重新生成会丢弃修改
+            KeyCode::Esc => {
+                draft.editor = None;
+                draft.message = None;
+            }
+            KeyCode::Char('s') if key_event.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                let (name, content) = (draft.name.clone(), editor.text.clone());
+                match self.manuscripts.create(&name, &content) {
+                    Ok(path) => {
+                        self.draft = None;
+                        self.manuscripts.message = Some(format!("Saved {}", path.display()));
+                        self.manuscripts.viewing = false;
+                        self.manuscripts.scroll = 0;
+                        self.current_tab = 1;
+                    }
+                    Err(e) => draft.message = Some(e),
+                }
+            }
+            _ => {
+                editor.handle_key(key_event);
+            }
+        }
+    }
+
+    // 新建的 manuscript 读取 NETWORK 页当前选中的表
+    fn new_manuscript_template(&self, name: &str) -> String {
+        let table = self.selected_table()
+            .or_else(|| self.catalog.tables(self.selected_chain_index).first());
+        match table {
+            Some(table) => manuscript::template(name, &table.id.chain, &table.id.table),
+            None => manuscript::template(name, "ethereum", "blocks"),
+        }
+    }
+
+    // 后台 setup 正在运行且还没有请求取消
+    fn setup_cancellable(&self) -> bool {
+        self.state == AppState::Started && self.docker_setup_in_progress && !self.setup_cancel.is_cancelled()
+    }
+
+    fn start_setup(&mut self) {
+        self.state = AppState::Started;
+        if self.docker_setup_in_progress {
+            return;
+        }
+        self.setup_cancel = CancelHandle::default();
+        self.pull_progress = None;
+        self.docker_setup_in_progress = true;
+        self.setup_state = SetupState::InProgress;
+        self.current_setup_step = None;
+        self.setup_attempts.clear();
+        self.port_conflicts.clear();
+        tokio::spawn({
+            let mut app = self.clone();
+            async move {
+                app.setup_docker().await;
+            }
+        });
+    }
+
+    // 当前右侧面板显示的表格：有保存的 SQL 时为查询结果，否则为示例数据
+    pub fn results_grid_visible(&self) -> bool {
+        self.show_tables && self.selected_table_index.is_some() && self.saved_sql.is_some()
+    }
+
+    fn active_grid_size(&self) -> Option<(usize, usize)> {
+        if self.results_grid_visible() {
+            Some((self.sql_columns.len(), self.sql_data.len()))
+        } else if self.show_tables {
+            self.example_data.as_ref().map(|data| (data.columns.len(), data.data.len()))
+        } else {
+            None
+        }
+    }
+
+    fn handle_finder_key_event(&mut self, key_event: KeyEvent, visible_height: usize) {
+        let Some(finder) = &mut self.finder else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Esc => self.finder = None,
+            KeyCode::Up => finder.up(),
+            KeyCode::Down => finder.down(),
+            KeyCode::Backspace => finder.pop(),
+            KeyCode::Char(c) => finder.push(c),
+            KeyCode::Enter => {
+                let target = finder.selected_item().map(|item| item.target);
+                self.finder = None;
+                if let Some(target) = target {
+                    self.jump_to(target, visible_height);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 跳转到命令面板中选中的链、表或字段
+    fn jump_to(&mut self, target: FinderTarget, visible_height: usize) {
+        let (chain, table, column) = match target {
+            FinderTarget::Chain { chain } => (chain, None, None),
+            FinderTarget::Table { chain, table } => (chain, Some(table), None),
+            FinderTarget::Column { chain, table, column } => (chain, Some(table), Some(column)),
+        };
+
+        self.selected_chain_index = chain;
+        if chain < self.scroll_offset || chain >= self.scroll_offset + visible_height {
+            self.scroll_offset = chain.saturating_sub(visible_height / 2);
+        }
+        self.saved_sql = None;
+        self.grid_focused = false;
+        self.show_tables = table.is_some();
+        self.selected_table_index = table;
+        self.update_example_data();
+
+        if let Some(column) = column {
+            self.highlighted_column = Some(column);
+            // 示例数据表格也定位到同名字段
+            let name = self.selected_table().and_then(|t| t.columns.get(column)).map(|c| c.name.clone());
+            if let (Some(name), Some(example_data)) = (name, &self.example_data) {
+                if let Some(index) = example_data.columns.iter().position(|c| c.name == name) {
+                    self.example_grid.column = index;
+                }
+            }
+        }
+    }
+
+    fn handle_grid_key_event(&mut self, key_event: KeyEvent, visible_height: usize) {
+        let Some((columns, rows)) = self.active_grid_size() else {
+            self.grid_focused = false;
+            return;
+        };
+        let grid = if self.results_grid_visible() {
+            &mut self.results_grid
+        } else {
+            &mut self.example_grid
+        };
+
+        if grid.show_cell {
+            if matches!(key_event.code, KeyCode::Esc | KeyCode::Enter) {
+                grid.show_cell = false;
+            }
+            return;
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('f') => self.grid_focused = false,
+            KeyCode::Char('q') => self.exit = true,
+            KeyCode::Up => grid.up(1),
+            KeyCode::Down => grid.down(1, rows),
+            KeyCode::PageUp => grid.up(visible_height / 2),
+            KeyCode::PageDown => grid.down(visible_height / 2, rows),
+            KeyCode::Home => grid.first_row(),
+            KeyCode::End => grid.last_row(rows),
+            KeyCode::Left => grid.left(),
+            KeyCode::Right => grid.right(columns),
+            KeyCode::Enter => grid.show_cell = rows > 0,
+            _ => {}
+        }
+    }
+
+    // 当前选中的表，所有按索引查找表的地方都通过 catalog
+    pub fn selected_table(&self) -> Option<&CatalogTable> {
+        self.catalog.table(self.selected_chain_index, self.selected_table_index?)
+    }
+
     // Add new method to generate initial SQL
     fn generate_initial_sql(&self) -> String {
-        if let Some(chain) = self.chains.get(self.selected_chain_index) {
-            if let Some(table_index) = self.selected_table_index {
-                if let Some(table_name) = chain.dataDictionary.keys().nth(table_index) {
-                    return format!("SELECT *\nFROM {}.{}\nLIMIT 10", chain.name.to_lowercase(), table_name);
+        self.selected_table()
+            .map(|table| format!("SELECT *\nFROM {}\nLIMIT 10", table.id))
+            .unwrap_or_default()
+    }
+
+    // 光标前只有空白
+    fn at_line_start(&self) -> bool {
+        let before = &self.sql_editor.text[..self.sql_editor.cursor];
+        before[before.rfind('\n').map(|pos| pos + 1).unwrap_or(0)..].trim().is_empty()
+    }
+
+    // 打开补全弹窗；insert_single 时唯一的候选直接插入，没有候选时 Tab 照常缩进
+    fn complete(&mut self, insert_single: bool) {
+        let completion = Completion::new(&self.sql_editor.text, self.sql_editor.cursor, &self.catalog, self.selected_chain_index);
+        match completion {
+            Some(completion) if insert_single && completion.candidates.len() == 1 && !completion.range.is_empty() => {
+                self.accept_completion(completion);
+            }
+            Some(completion) => self.completion = Some(completion),
+            None if insert_single => self.sql_editor.insert_str("  "),
+            None => {}
+        }
+    }
+
+    fn accept_completion(&mut self, completion: Completion) {
+        if let Some(candidate) = completion.selected_candidate() {
+            self.sql_editor.replace(completion.range.clone(), &candidate.text);
+        }
+    }
+
+    // 不处理的按键关闭弹窗后交给编辑器
+    fn handle_completion_key_event(&mut self, key_event: KeyEvent) -> bool {
+        let Some(completion) = self.completion.as_mut() else {
+            return false;
+        };
+        let control = key_event.modifiers.contains(event::KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Up => completion.up(),
+            KeyCode::Down => completion.down(),
+            KeyCode::Esc => self.completion = None,
+            KeyCode::Tab | KeyCode::Enter if !control => {
+                if let Some(completion) = self.completion.take() {
+                    self.accept_completion(completion);
                 }
             }
+            // 输入名字的一部分时继续过滤，空格和标点关闭弹窗
+            KeyCode::Char(c) if !control && (c.is_alphanumeric() || c == '_') => {
+                self.sql_editor.handle_key(key_event);
+                self.completion = Completion::new(&self.sql_editor.text, self.sql_editor.cursor, &self.catalog, self.selected_chain_index);
+            }
+            KeyCode::Backspace if !completion.range.is_empty() => {
+                self.sql_editor.handle_key(key_event);
+                self.completion = Completion::new(&self.sql_editor.text, self.sql_editor.cursor, &self.catalog, self.selected_chain_index);
+            }
+            _ => {
+                self.completion = None;
+                return false;
+            }
         }
-        String::new()
+        true
     }
 
-    // Add a new method to handle SQL response
+    // 提交 SQL 到本地 manuscript-debug 容器，结果通过 sql_sender 通道返回
+    fn execute_sql(&mut self) {
+        let sql = self.sql_editor.text.trim().to_string();
+        if sql.is_empty() || self.sql_executing {
+            return;
+        }
+
+        self.saved_sql = Some(self.sql_editor.text.clone());
+        self.show_sql_window = false;
+        self.sql_executing = true;
+        self.sql_timer = 0;
+        self.sql_error = None;
+        self.sql_stats = None;
+        self.sql_result = None;
+        self.sql_columns.clear();
+        self.sql_data.clear();
+        self.results_grid.reset();
+
+        let client = SqlClient::new(&self.docker_manager.sql_endpoint());
+        let sender = self.sql_sender.clone();
+        if let Some(sender) = sender {
+            tokio::spawn(async move {
+                client.execute(&sql, sender).await;
+            });
+        }
+    }
+
+    // 处理 statement 协议的一页响应，行数据逐页累加
     pub fn handle_sql_response(&mut self, json: serde_json::Value) {
+        if let Some(stats) = json.get("stats") {
+            self.sql_stats = serde_json::from_value(stats.clone()).ok();
+        }
 
         if let Some(error) = json.get("error") {
-            self.sql_error = Some(error.to_string());
+            self.sql_error = Some(serde_json::from_value(error.clone())
+                .unwrap_or_else(|_| QueryError::transport(error.to_string())));
             self.sql_executing = false;
+            self.sql_result = Some("Query failed".to_string());
             return;
         }
 
-        // Process columns if available
-        if let Some(columns) = json.get("columns").and_then(|c| c.as_array()) {
-            self.sql_columns = columns.iter()
-                .filter_map(|col| {
-                    Some(Column {
-                        name: col.get("name")?.as_str()?.to_string(),
-                        type_: col.get("type")?.as_str()?.to_string(),
-                    })
-                })
-                .collect();
+        // Columns arrive once the query is planned
+        if self.sql_columns.is_empty() {
+            self.sql_columns = sql::parse_columns(&json);
         }
 
-        // Process data if available
         if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
-            self.sql_data = data.iter()
-                .filter_map(|row| row.as_array().cloned())
-                .collect();
+            self.sql_data.extend(data.iter().filter_map(|row| row.as_array().cloned()));
         }
 
-        // Update status
-        if let Some(stats) = json.get("stats") {
-            if let Some(state) = stats.get("state").and_then(|s| s.as_str()) {
-                self.sql_result = Some(format!("Query status: {}", state));
-                if state == "FINISHED" {
-                    println!("Debug: {}", self.sql_data.len());
-                    self.sql_executing = false;
-                    self.sql_result = Some(format!("Query completed: {} rows returned", self.sql_data.len()));
-                }
-            }
+        // 没有 nextUri 说明查询已经结束
+        if json.get("nextUri").is_none() {
+            self.sql_executing = false;
+            self.sql_result = Some(format!("Query completed: {} rows returned", self.sql_data.len()));
+        } else {
+            self.sql_result = Some(format!("Fetched {} rows...", self.sql_data.len()));
         }
     }
 
@@ -867,26 +1868,19 @@ impl App {
         // 克隆sender用于Docker管理器
         let sender = self.update_sender.clone();
         
-        match self.docker_manager.setup(sender).await {
+        let update = match self.docker_manager.setup(sender, &self.setup_cancel).await {
             Ok(msg) => {
-                if !self.should_cancel_setup {
-                    if let Some(sender) = &self.update_sender {
-                        let _ = sender.send(AppUpdate::SetupComplete).await;
-                        let _ = sender.send(AppUpdate::DockerStatus(msg)).await;
-                    }
+                if let Some(sender) = &self.update_sender {
+                    let _ = sender.send(AppUpdate::SetupComplete).await;
                 }
+                AppUpdate::DockerStatus(msg)
             },
-            Err(e) => {
-                if !self.should_cancel_setup {
-                    if let Some(sender) = &self.update_sender {
-                        // 发送失败状态，包含当前步骤
-                        let _ = sender.send(AppUpdate::SetupFailed(
-                            e.to_string(),
-                            self.current_setup_step.clone().unwrap_or(SetupStep::CheckingDocker)
-                        )).await;
-                    }
-                }
-            }
+            // 发送失败状态，包含失败的步骤
+            Err(SetupError::Failed { step, message }) => AppUpdate::SetupFailed(message, step),
+            Err(SetupError::Cancelled(message)) => AppUpdate::SetupCancelled(message),
+        };
+        if let Some(sender) = &self.update_sender {
+            let _ = sender.send(update).await;
         }
         
         self.docker_setup_in_progress = false;
@@ -904,22 +1898,15 @@ impl App {
             Line::from("")
         ];
 
-        // 定义所有步骤
-        let all_steps = vec![
-            SetupStep::CheckingDocker,
-            SetupStep::PullingImage,
-            SetupStep::StartingContainer,
-            SetupStep::ConfiguringNetwork,
-            SetupStep::VerifyingSetup,
-        ];
-
         // 根据当前步骤显示进度
-        for step in all_steps {
+        for step in SetupStep::ALL {
             let (prefix, style) = match &self.current_setup_step {
                 Some(current) => {
                     if *current == step {
                         match &self.setup_state {
                             SetupState::Failed(_) => ("✗", Style::default().fg(Color::Red)),
+                            SetupState::Cancelled => ("⊘", Style::default().fg(Color::DarkGray)),
+                            _ if self.setup_step_complete => ("✓", Style::default().fg(Color::Green)),
                             _ => ("⋯", Style::default().fg(Color::Yellow))
                         }
                     } else if *current > step {
@@ -935,6 +1922,26 @@ impl App {
                 format!("{} {}", prefix, step.as_str()),
                 style
             )));
+
+            // 该步骤每次失败的尝试及错误
+            for (_, attempt) in self.setup_attempts.iter().filter(|(s, _)| *s == step) {
+                let retry = match attempt.retry_in {
+                    Some(delay) => format!(", retrying in {}s", delay.as_secs()),
+                    None => String::new(),
+                };
+                lines.push(Line::from(Span::styled(
+                    format!("    attempt {}/{} failed{}: {}", attempt.number, attempt.attempts, retry, attempt.error),
+                    Style::default().fg(Color::DarkGray)
+                )));
+            }
+        }
+
+        if !self.port_conflicts.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Press a to retry with free ports, or set sql_port/job_port in the config",
+                Style::default().fg(Color::Yellow)
+            )));
         }
 
         // 添加状态消息，如果是错误状态则显示为红色
@@ -957,6 +1964,10 @@ impl App {
     }
 }
 
+const SAMPLE_ROWS: usize = 10;
+const CONTAINER_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const JOBS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 const GAUGE1_COLOR: Color = tailwind::RED.c800;
 const CUSTOM_LABEL_COLOR: Color = tailwind::SLATE.c200;
 const GAUGE2_COLOR: Color = tailwind::GREEN.c800;
@@ -976,14 +1987,29 @@ struct ChainData {
     name: String,
     status: String,
     lastUpdate: String,
-    dataDictionary: DataDictionary,
+    // 任意表名 → 字段列表，新增的表会自动出现在界面中
+    #[serde(default, deserialize_with = "null_as_default")]
+    dataDictionary: IndexMap<String, Vec<DataDictionaryItem>>,
 }
 
-#[derive(Debug, Deserialize)]
-struct DataDictionary {
-    blocks: Vec<DataDictionaryItem>,
-    transactions: Vec<DataDictionaryItem>,
-    transactionLogs: Vec<DataDictionaryItem>,
+// API 中缺失或为 null 的字段按默认值处理
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+// 带标题的进度条，alert 为 true 时用红色
+pub fn render_gauge(title: &str, ratio: f64, label: String, alert: bool, area: Rect, buf: &mut Buffer) {
+    let label = Span::styled(label, Style::new().italic().bold().fg(CUSTOM_LABEL_COLOR));
+    Gauge::default()
+        .block(title_block(title))
+        .gauge_style(if alert { GAUGE1_COLOR } else { GAUGE2_COLOR })
+        .ratio(ratio.clamp(0.0, 1.0))
+        .label(label)
+        .render(area, buf);
 }
 
 pub fn title_block(title: &str) -> Block {
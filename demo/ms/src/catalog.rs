@@ -0,0 +1,129 @@
+use std::{fmt, str::FromStr};
+use crate::app::{Chain, DataDictionaryItem};
+
+// 表的稳定标识，格式与 SQL 中引用的 chain.table 一致
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TableId {
+    pub chain: String,
+    pub table: String,
+}
+
+impl TableId {
+    pub fn new(chain_name: &str, table: &str) -> Self {
+        Self {
+            chain: chain_name.to_lowercase(),
+            table: table.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for TableId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.chain, self.table)
+    }
+}
+
+// 表列表的排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TableSort {
+    #[default]
+    Api,
+    Name,
+}
+
+impl FromStr for TableSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "api" => Ok(TableSort::Api),
+            "name" => Ok(TableSort::Name),
+            other => Err(format!("unknown table sort '{}', expected 'api' or 'name'", other)),
+        }
+    }
+}
+
+impl fmt::Display for TableSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableSort::Api => write!(f, "api"),
+            TableSort::Name => write!(f, "name"),
+        }
+    }
+}
+
+// 表示数据新旧的字段，按优先级排列
+const RECENCY_COLUMNS: [&str; 2] = ["block_number", "block_timestamp"];
+
+#[derive(Debug, Clone)]
+pub struct CatalogTable {
+    pub id: TableId,
+    pub name: String,
+    pub columns: Vec<DataDictionaryItem>,
+}
+
+impl CatalogTable {
+    // 用于取最新若干行的排序字段，没有时返回 None
+    pub fn recency_column(&self) -> Option<&str> {
+        RECENCY_COLUMNS.iter()
+            .find(|name| self.columns.iter().any(|column| column.name == **name))
+            .copied()
+    }
+}
+
+// chain → 有序的表 → 字段，表列表、SQL 模板和示例数据共用同一份顺序
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    names: Vec<String>,  // 小写的链名，与 TableId.chain 一致
+    chains: Vec<Vec<CatalogTable>>,
+}
+
+impl Catalog {
+    pub fn build(chains: &[Chain], sort: TableSort) -> Self {
+        let names = chains.iter().map(|chain| chain.name.to_lowercase()).collect();
+        let chains = chains.iter()
+            .map(|chain| {
+                let mut tables: Vec<CatalogTable> = chain.dataDictionary
+                    .iter()
+                    .map(|(name, columns)| CatalogTable {
+                        id: TableId::new(&chain.name, name),
+                        name: name.clone(),
+                        columns: columns.clone(),
+                    })
+                    .collect();
+                if sort == TableSort::Name {
+                    tables.sort_by_key(|table| table.name.to_lowercase());
+                }
+                tables
+            })
+            .collect();
+        Self { names, chains }
+    }
+
+    pub fn tables(&self, chain_index: usize) -> &[CatalogTable] {
+        self.chains.get(chain_index).map(|t| t.as_slice()).unwrap_or_default()
+    }
+
+    pub fn table(&self, chain_index: usize, table_index: usize) -> Option<&CatalogTable> {
+        self.tables(chain_index).get(table_index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    // 按 SQL 中的写法查找链，忽略大小写
+    pub fn chain_index(&self, chain: &str) -> Option<usize> {
+        let chain = chain.to_lowercase();
+        self.names.iter().position(|name| *name == chain)
+    }
+
+    pub fn find(&self, chain: &str, table: &str) -> Option<&CatalogTable> {
+        let chain_index = self.chain_index(chain)?;
+        self.tables(chain_index).iter().find(|t| t.name == table)
+    }
+}
@@ -0,0 +1,285 @@
+use std::ops::Range;
+use crate::catalog::{Catalog, CatalogTable};
+use crate::lexer::{self, Token, TokenKind};
+
+// 决定补全表还是字段的子句关键字
+const CLAUSES: &[&str] = &["SELECT", "FROM", "JOIN", "WHERE", "ON", "USING", "BY", "HAVING", "LIMIT", "SET"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Table,
+    Column,
+}
+
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub text: String,         // 插入编辑器的文本
+    pub data_type: String,
+    pub description: String,
+    pub table: Option<String>,  // 查询引用了多张表时字段所属的表
+}
+
+// SQL 编辑器的补全弹窗，range 为光标前被替换的部分
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub kind: CompletionKind,
+    pub range: Range<usize>,
+    pub candidates: Vec<Candidate>,
+    pub selected: usize,
+}
+
+impl Completion {
+    // FROM/JOIN 后面补全 chain.table，其他位置补全查询引用的表的字段；没有候选时返回 None
+    pub fn new(sql: &str, cursor: usize, catalog: &Catalog, chain_index: usize) -> Option<Self> {
+        let bytes = sql.as_bytes();
+        let mut start = cursor;
+        while start > 0 && is_word(bytes[start - 1]) {
+            start -= 1;
+        }
+        let prefix = &sql[start..cursor];
+        if prefix.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let tokens = lexer::tokenize(&sql[..start]);
+        // 字符串和注释里不补全
+        if tokens.last().is_some_and(|token| {
+            token.span.end == start && matches!(token.kind, TokenKind::String | TokenKind::Comment)
+        }) {
+            return None;
+        }
+
+        // 光标前的 qualifier.，例如 ethereum. 或 b.
+        let mut end = tokens.len();
+        let mut qualifier = None;
+        if end >= 2 && tokens[end - 1].is(sql, ".") && tokens[end - 1].span.end == start && tokens[end - 2].is_identifier() {
+            qualifier = Some(tokens[end - 2].name(sql));
+            end -= 2;
+        }
+        let meaningful: Vec<&Token> = tokens[..end].iter().filter(|token| token.kind != TokenKind::Comment).collect();
+        let previous = meaningful.last();
+        let clause = meaningful.iter().rev()
+            .find(|token| token.kind == TokenKind::Keyword && CLAUSES.iter().any(|clause| token.is_keyword(sql, clause)))
+            .map(|token| token.text(sql).to_uppercase());
+        // FROM a, b 中逗号后面也是表
+        let after_from = previous.is_some_and(|token| token.is_keyword(sql, "FROM") || token.is_keyword(sql, "JOIN"))
+            || (previous.is_some_and(|token| token.is(sql, ",")) && clause.as_deref() == Some("FROM"));
+
+        let (kind, candidates) = if after_from {
+            (CompletionKind::Table, table_candidates(catalog, chain_index, qualifier, prefix))
+        } else {
+            let scope = tables_in_scope(sql, catalog, chain_index);
+            let tables: Vec<&CatalogTable> = match qualifier {
+                Some(qualifier) => scope.iter()
+                    .filter(|(table, alias)| table.name == qualifier || *alias == Some(qualifier))
+                    .map(|(table, _)| *table)
+                    .collect(),
+                // 同一张表 join 两次时字段只列一遍
+                None => scope.iter().enumerate()
+                    .filter(|(i, (table, _))| !scope[..*i].iter().any(|(known, _)| known.id == table.id))
+                    .map(|(_, (table, _))| *table)
+                    .collect(),
+            };
+            (CompletionKind::Column, column_candidates(&tables, prefix))
+        };
+
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(Self { kind, range: start..cursor, candidates, selected: 0 })
+    }
+
+    pub fn selected_candidate(&self) -> Option<&Candidate> {
+        self.candidates.get(self.selected)
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.candidates.len() {
+            self.selected += 1;
+        }
+    }
+}
+
+fn is_word(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80
+}
+
+// 忽略大小写的前缀匹配
+fn matches(name: &str, prefix: &str) -> bool {
+    name.len() >= prefix.len() && name.is_char_boundary(prefix.len()) && name[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+// 选中链的表排在前面；输入的前缀可以是链名也可以是表名
+fn table_candidates(catalog: &Catalog, chain_index: usize, chain: Option<&str>, prefix: &str) -> Vec<Candidate> {
+    let candidate = |table: &CatalogTable, text: String| Candidate {
+        text,
+        data_type: "table".to_string(),
+        description: format!("{} columns", table.columns.len()),
+        table: None,
+    };
+
+    // ethereum. 后面只补全表名
+    if let Some(chain) = chain {
+        let Some(index) = catalog.chain_index(chain) else {
+            return Vec::new();
+        };
+        return catalog.tables(index).iter()
+            .filter(|table| matches(&table.name, prefix))
+            .map(|table| candidate(table, table.name.clone()))
+            .collect();
+    }
+
+    let others = (0..catalog.len()).filter(|&index| index != chain_index);
+    std::iter::once(chain_index).chain(others)
+        .flat_map(|index| catalog.tables(index))
+        .filter(|table| matches(&table.id.to_string(), prefix) || matches(&table.name, prefix))
+        .map(|table| candidate(table, table.id.to_string()))
+        .collect()
+}
+
+fn column_candidates(tables: &[&CatalogTable], prefix: &str) -> Vec<Candidate> {
+    tables.iter()
+        .flat_map(|table| table.columns.iter().map(move |column| (table, column)))
+        .filter(|(_, column)| matches(&column.name, prefix))
+        .map(|(table, column)| Candidate {
+            text: column.name.clone(),
+            data_type: column.dataType.clone(),
+            description: column.description.clone(),
+            table: (tables.len() > 1).then(|| table.name.clone()),
+        })
+        .collect()
+}
+
+// 查询中 chain.table 引用的表及其别名；还没有写 FROM 时使用选中链的所有表
+fn tables_in_scope<'a>(sql: &'a str, catalog: &'a Catalog, chain_index: usize) -> Vec<(&'a CatalogTable, Option<&'a str>)> {
+    let tokens = lexer::tokenize(sql);
+    let tokens: Vec<&Token> = tokens.iter().filter(|token| token.kind != TokenKind::Comment).collect();
+    let is = |i: usize, text: &str| tokens.get(i).is_some_and(|token| token.is(sql, text));
+    let is_identifier = |i: usize| tokens.get(i).is_some_and(|token| token.is_identifier());
+
+    let mut scope: Vec<(&CatalogTable, Option<&str>)> = Vec::new();
+    for i in 0..tokens.len() {
+        if !(is_identifier(i) && is(i + 1, ".") && is_identifier(i + 2)) || (i > 0 && is(i - 1, ".")) {
+            continue;
+        }
+        let Some(table) = catalog.find(tokens[i].name(sql), tokens[i + 2].name(sql)) else {
+            continue;
+        };
+        // FROM ethereum.blocks b 或 FROM ethereum.blocks AS b
+        let mut next = i + 3;
+        if tokens.get(next).is_some_and(|token| token.is_keyword(sql, "AS")) {
+            next += 1;
+        }
+        let alias = (is_identifier(next) && !is(next + 1, ".") && !is(next + 1, "(")).then(|| tokens[next].name(sql));
+        if !scope.iter().any(|(known, known_alias)| known.id == table.id && *known_alias == alias) {
+            scope.push((table, alias));
+        }
+    }
+    if scope.is_empty() {
+        scope = catalog.tables(chain_index).iter().map(|table| (table, None)).collect();
+    }
+    scope
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use crate::app::{Chain, DataDictionaryItem};
+    use crate::catalog::TableSort;
+    use super::*;
+
+    fn chain(name: &str, tables: &[(&str, &[&str])]) -> Chain {
+        let data_dictionary: IndexMap<String, Vec<DataDictionaryItem>> = tables.iter()
+            .map(|(table, columns)| {
+                let columns = columns.iter()
+                    .map(|column| DataDictionaryItem { name: column.to_string(), dataType: "bigint".to_string(), description: String::new() })
+                    .collect();
+                (table.to_string(), columns)
+            })
+            .collect();
+        Chain {
+            name: name.to_string(),
+            status: "Online".to_string(),
+            lastUpdate: String::new(),
+            time_ago: String::new(),
+            dataDictionary: data_dictionary,
+        }
+    }
+
+    fn catalog() -> Catalog {
+        Catalog::build(&[
+            chain("Polygon", &[("blocks", &["block_number", "miner"])]),
+            chain("Ethereum", &[
+                ("blocks", &["block_number", "hash"]),
+                ("transactions", &["hash", "from_address", "block_number"]),
+            ]),
+        ], TableSort::Api)
+    }
+
+    // | 标记光标位置；选中的链是 ethereum
+    fn complete(sql: &str) -> Option<(CompletionKind, Vec<String>)> {
+        let cursor = sql.find('|').unwrap();
+        let sql = sql.replacen('|', "", 1);
+        Completion::new(&sql, cursor, &catalog(), 1)
+            .map(|completion| (completion.kind, completion.candidates.into_iter().map(|candidate| candidate.text).collect()))
+    }
+
+    fn texts(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn tables_after_from_start_with_the_selected_chain() {
+        assert_eq!(complete("SELECT * FROM |"), Some((CompletionKind::Table, texts(&[
+            "ethereum.blocks", "ethereum.transactions", "polygon.blocks",
+        ]))));
+    }
+
+    #[test]
+    fn tables_after_join_are_filtered_by_prefix() {
+        assert_eq!(
+            complete("SELECT * FROM ethereum.blocks b JOIN tra|"),
+            Some((CompletionKind::Table, texts(&["ethereum.transactions"]))),
+        );
+    }
+
+    #[test]
+    fn table_names_after_a_chain_qualifier() {
+        assert_eq!(complete("SELECT * FROM ethereum.|"), Some((CompletionKind::Table, texts(&["blocks", "transactions"]))));
+        assert_eq!(complete("SELECT * FROM polygon.bl|"), Some((CompletionKind::Table, texts(&["blocks"]))));
+        assert_eq!(complete("SELECT * FROM solana.|"), None);
+    }
+
+    #[test]
+    fn columns_of_the_tables_in_the_query() {
+        assert_eq!(
+            complete("SELECT | FROM ethereum.blocks"),
+            Some((CompletionKind::Column, texts(&["block_number", "hash"]))),
+        );
+    }
+
+    #[test]
+    fn columns_are_scoped_to_an_alias() {
+        let sql = "SELECT t.| FROM ethereum.blocks b JOIN ethereum.transactions t ON b.block_number = t.block_number";
+        assert_eq!(complete(sql), Some((CompletionKind::Column, texts(&["hash", "from_address", "block_number"]))));
+        let sql = "SELECT b.h| FROM ethereum.blocks AS b JOIN ethereum.transactions t ON true";
+        assert_eq!(complete(sql), Some((CompletionKind::Column, texts(&["hash"]))));
+    }
+
+    #[test]
+    fn no_candidates_inside_strings_or_comments() {
+        assert_eq!(complete("SELECT * FROM ethereum.blocks WHERE hash = 'bl|"), None);
+        assert_eq!(complete("SELECT * FROM ethereum.blocks -- bl|"), None);
+        assert_eq!(complete("SELECT * /* FROM | */ FROM ethereum.blocks"), None);
+    }
+
+    #[test]
+    fn no_candidates_for_numbers_or_unmatched_prefixes() {
+        assert_eq!(complete("SELECT * FROM ethereum.blocks LIMIT 1|"), None);
+        assert_eq!(complete("SELECT zz| FROM ethereum.blocks"), None);
+    }
+}
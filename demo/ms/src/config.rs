@@ -0,0 +1,548 @@
+use std::{collections::BTreeMap, env, fmt, fs, path::PathBuf, str::FromStr, time::Duration};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use crate::app::SetupStep;
+use crate::catalog::TableSort;
+use crate::metadata::NETWORK_CHAINS_URL;
+
+pub const DEFAULT_IMAGE: &str = "repository.chainbase.com/manuscript-node/manuscript-debug:v0.0.1";
+pub const DEFAULT_CONTAINER_NAME: &str = "manuscript-debug";
+pub const DEFAULT_SQL_PORT: u16 = 18083;
+pub const DEFAULT_JOB_PORT: u16 = 18081;
+pub const DEFAULT_PROFILE: &str = "default";
+pub const DEFAULT_WORKSPACE_DIR: &str = "manuscripts";
+// 未指定端口的命名环境，依次在默认端口基础上偏移
+const PROFILE_PORT_STEP: u16 = 10;
+
+// 配置值的来源，优先级从低到高：默认值 < 配置文件 < 环境变量 < 命令行参数
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    Default,
+    File(PathBuf),
+    Env(&'static str),
+    Cli(&'static str),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File(path) => write!(f, "file {}", path.display()),
+            ConfigSource::Env(name) => write!(f, "env {}", name),
+            ConfigSource::Cli(flag) => write!(f, "flag {}", flag),
+        }
+    }
+}
+
+// 每个 setup 步骤的超时和重试策略，重试间隔按指数增长
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub timeout: Duration,
+    pub retries: u32,
+    pub backoff: Duration,
+    // 多久没有进度事件就判定本次尝试失败，目前只用于拉取镜像
+    pub idle_timeout: Option<Duration>,
+}
+
+impl RetryPolicy {
+    const fn new(timeout_secs: u64, retries: u32, backoff_secs: u64) -> Self {
+        Self {
+            timeout: Duration::from_secs(timeout_secs),
+            retries,
+            backoff: Duration::from_secs(backoff_secs),
+            idle_timeout: None,
+        }
+    }
+
+    const fn with_idle_timeout(mut self, idle_secs: u64) -> Self {
+        self.idle_timeout = Some(Duration::from_secs(idle_secs));
+        self
+    }
+
+    fn default_for(step: &SetupStep) -> Self {
+        match step {
+            SetupStep::CheckingDocker => Self::new(10, 2, 1),
+            SetupStep::PullingImage => Self::new(1800, 3, 2).with_idle_timeout(120),
+            SetupStep::StartingContainer => Self::new(60, 1, 2),
+            SetupStep::ConfiguringNetwork => Self::new(10, 0, 1),
+            SetupStep::VerifyingSetup => Self::new(120, 0, 1),
+        }
+    }
+
+    // 第 attempt 次失败后的等待时间：backoff, 2×backoff, 4×backoff...
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+
+    fn merge(&mut self, file: &FilePolicy) {
+        if let Some(timeout) = file.timeout_secs {
+            self.timeout = Duration::from_secs(timeout);
+        }
+        if let Some(retries) = file.retries {
+            self.retries = retries;
+        }
+        if let Some(backoff) = file.backoff_secs {
+            self.backoff = Duration::from_secs(backoff);
+        }
+        if let Some(idle) = file.idle_timeout_secs {
+            self.idle_timeout = Some(Duration::from_secs(idle));
+        }
+    }
+}
+
+impl fmt::Display for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f, "timeout {}s, {} retries, backoff {}s",
+            self.timeout.as_secs(), self.retries, self.backoff.as_secs()
+        )?;
+        if let Some(idle) = self.idle_timeout {
+            write!(f, ", idle timeout {}s", idle.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: ConfigSource,
+}
+
+impl<T> Setting<T> {
+    fn new(value: T) -> Self {
+        Self { value, source: ConfigSource::Default }
+    }
+
+    fn set(&mut self, value: Option<T>, source: ConfigSource) {
+        if let Some(value) = value {
+            self.value = value;
+            self.source = source;
+        }
+    }
+}
+
+// 命令行传入的覆盖值
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub metadata_url: Option<String>,
+    pub image: Option<String>,
+    pub container_name: Option<String>,
+    pub sql_port: Option<u16>,
+    pub job_port: Option<u16>,
+    pub auto_ports: Option<bool>,
+    pub table_sort: Option<TableSort>,
+    pub demo: Option<bool>,
+    pub profile: Option<String>,
+    pub workspace: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    metadata_url: Option<String>,
+    image: Option<String>,
+    container_name: Option<String>,
+    sql_port: Option<u16>,
+    job_port: Option<u16>,
+    auto_ports: Option<bool>,
+    table_sort: Option<String>,
+    demo: Option<bool>,
+    profile: Option<String>,
+    workspace: Option<PathBuf>,
+    #[serde(default)]
+    setup: FileSetup,
+    #[serde(default)]
+    profiles: IndexMap<String, FileProfile>,
+}
+
+// [profiles.<name>]：一个独立的调试环境
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileProfile {
+    image: Option<String>,
+    container_name: Option<String>,
+    sql_port: Option<u16>,
+    job_port: Option<u16>,
+}
+
+// 一个调试环境：各自的镜像、容器和端口
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub image: String,
+    pub container_name: String,
+    pub sql_port: u16,
+    pub job_port: u16,
+}
+
+// [setup.<step>] 表，例如 [setup.pull] timeout_secs = 900
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileSetup {
+    docker: Option<FilePolicy>,
+    pull: Option<FilePolicy>,
+    start: Option<FilePolicy>,
+    network: Option<FilePolicy>,
+    verify: Option<FilePolicy>,
+}
+
+impl FileSetup {
+    fn get(&self, step: &SetupStep) -> Option<&FilePolicy> {
+        match step {
+            SetupStep::CheckingDocker => self.docker.as_ref(),
+            SetupStep::PullingImage => self.pull.as_ref(),
+            SetupStep::StartingContainer => self.start.as_ref(),
+            SetupStep::ConfiguringNetwork => self.network.as_ref(),
+            SetupStep::VerifyingSetup => self.verify.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilePolicy {
+    timeout_secs: Option<u64>,
+    retries: Option<u32>,
+    backoff_secs: Option<u64>,
+    idle_timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub path: Option<PathBuf>,
+    pub metadata_url: Setting<String>,
+    pub image: Setting<String>,
+    pub container_name: Setting<String>,
+    pub sql_port: Setting<u16>,
+    pub job_port: Setting<u16>,
+    pub auto_ports: Setting<bool>,  // 端口被占用时自动选择空闲端口
+    pub table_sort: Setting<TableSort>,
+    pub demo: Setting<bool>,  // 示例数据使用内置的 mock 行
+    pub setup: BTreeMap<SetupStep, Setting<RetryPolicy>>,
+    pub profile: Setting<String>,  // 启动时使用的环境
+    pub profiles: Vec<Profile>,    // 第一个是由顶层配置组成的 default 环境
+    pub workspace: Setting<PathBuf>,  // 存放 manuscript 文件的目录
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            path: None,
+            metadata_url: Setting::new(NETWORK_CHAINS_URL.to_string()),
+            image: Setting::new(DEFAULT_IMAGE.to_string()),
+            container_name: Setting::new(DEFAULT_CONTAINER_NAME.to_string()),
+            sql_port: Setting::new(DEFAULT_SQL_PORT),
+            job_port: Setting::new(DEFAULT_JOB_PORT),
+            auto_ports: Setting::new(false),
+            table_sort: Setting::new(TableSort::default()),
+            demo: Setting::new(false),
+            setup: SetupStep::ALL.iter()
+                .map(|step| (step.clone(), Setting::new(RetryPolicy::default_for(step))))
+                .collect(),
+            profile: Setting::new(DEFAULT_PROFILE.to_string()),
+            profiles: Vec::new(),
+            workspace: Setting::new(default_workspace()),
+        }
+    }
+}
+
+// 与 manuscript-cli 相同，默认放在 ~/manuscripts 下
+fn default_workspace() -> PathBuf {
+    dirs::home_dir()
+        .map(|dir| dir.join(DEFAULT_WORKSPACE_DIR))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_WORKSPACE_DIR))
+}
+
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ms").join("config.toml"))
+}
+
+impl Config {
+    // An explicit path must exist; the default one is optional
+    pub fn load(path: Option<PathBuf>, overrides: Overrides) -> Result<Self, String> {
+        let mut config = Config::default();
+        let mut file_profiles = IndexMap::new();
+
+        let (path, required) = match path {
+            Some(path) => (Some(path), true),
+            None => (default_config_path(), false),
+        };
+        if let Some(path) = path {
+            if required || path.exists() {
+                let content = fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                let file: FileConfig = toml::from_str(&content)
+                    .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+                let source = ConfigSource::File(path.clone());
+                config.metadata_url.set(file.metadata_url, source.clone());
+                config.image.set(file.image, source.clone());
+                config.container_name.set(file.container_name, source.clone());
+                config.sql_port.set(file.sql_port, source.clone());
+                config.job_port.set(file.job_port, source.clone());
+                config.auto_ports.set(file.auto_ports, source.clone());
+                let table_sort = file.table_sort
+                    .map(|sort| sort.parse())
+                    .transpose()
+                    .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+                config.table_sort.set(table_sort, source.clone());
+                config.demo.set(file.demo, source.clone());
+                for (step, setting) in config.setup.iter_mut() {
+                    if let Some(policy) = file.setup.get(step) {
+                        let mut value = setting.value;
+                        value.merge(policy);
+                        setting.set(Some(value), source.clone());
+                    }
+                }
+                config.profile.set(file.profile, source.clone());
+                config.workspace.set(file.workspace, source);
+                file_profiles = file.profiles;
+                config.path = Some(path);
+            }
+        }
+
+        config.metadata_url.set(from_env("MS_METADATA_URL")?, ConfigSource::Env("MS_METADATA_URL"));
+        config.image.set(from_env("MS_IMAGE")?, ConfigSource::Env("MS_IMAGE"));
+        config.container_name.set(from_env("MS_CONTAINER_NAME")?, ConfigSource::Env("MS_CONTAINER_NAME"));
+        config.sql_port.set(from_env("MS_SQL_PORT")?, ConfigSource::Env("MS_SQL_PORT"));
+        config.job_port.set(from_env("MS_JOB_PORT")?, ConfigSource::Env("MS_JOB_PORT"));
+        config.auto_ports.set(from_env("MS_AUTO_PORTS")?, ConfigSource::Env("MS_AUTO_PORTS"));
+        config.table_sort.set(from_env("MS_TABLE_SORT")?, ConfigSource::Env("MS_TABLE_SORT"));
+        config.demo.set(from_env("MS_DEMO")?, ConfigSource::Env("MS_DEMO"));
+        config.profile.set(from_env("MS_PROFILE")?, ConfigSource::Env("MS_PROFILE"));
+        config.workspace.set(from_env("MS_WORKSPACE")?, ConfigSource::Env("MS_WORKSPACE"));
+
+        config.metadata_url.set(overrides.metadata_url, ConfigSource::Cli("--metadata-url"));
+        config.image.set(overrides.image, ConfigSource::Cli("--image"));
+        config.container_name.set(overrides.container_name, ConfigSource::Cli("--container-name"));
+        config.sql_port.set(overrides.sql_port, ConfigSource::Cli("--sql-port"));
+        config.job_port.set(overrides.job_port, ConfigSource::Cli("--job-port"));
+        config.auto_ports.set(overrides.auto_ports, ConfigSource::Cli("--auto-ports"));
+        config.table_sort.set(overrides.table_sort, ConfigSource::Cli("--table-sort"));
+        config.demo.set(overrides.demo, ConfigSource::Cli("--demo"));
+        config.profile.set(overrides.profile, ConfigSource::Cli("--profile"));
+        config.workspace.set(overrides.workspace, ConfigSource::Cli("--workspace"));
+
+        config.profiles = config.build_profiles(file_profiles)?;
+        if !config.profiles.iter().any(|profile| profile.name == config.profile.value) {
+            let names: Vec<&str> = config.profiles.iter().map(|profile| profile.name.as_str()).collect();
+            return Err(format!(
+                "Unknown profile '{}' ({}), expected one of: {}",
+                config.profile.value, config.profile.source, names.join(", ")
+            ));
+        }
+
+        Ok(config)
+    }
+
+    // 命名环境缺省的字段从 default 环境派生，避免容器名和端口冲突
+    fn build_profiles(&self, file_profiles: IndexMap<String, FileProfile>) -> Result<Vec<Profile>, String> {
+        let default = Profile {
+            name: DEFAULT_PROFILE.to_string(),
+            image: self.image.value.clone(),
+            container_name: self.container_name.value.clone(),
+            sql_port: self.sql_port.value,
+            job_port: self.job_port.value,
+        };
+        let mut profiles = vec![default.clone()];
+        for (index, (name, profile)) in file_profiles.into_iter().enumerate() {
+            if name == DEFAULT_PROFILE {
+                return Err(format!("Profile name '{}' is reserved for the top-level settings", DEFAULT_PROFILE));
+            }
+            let offset = PROFILE_PORT_STEP.saturating_mul(index as u16 + 1);
+            profiles.push(Profile {
+                image: profile.image.unwrap_or_else(|| default.image.clone()),
+                container_name: profile.container_name.unwrap_or_else(|| format!("{}-{}", default.container_name, name)),
+                sql_port: profile.sql_port.unwrap_or(default.sql_port.saturating_add(offset)),
+                job_port: profile.job_port.unwrap_or(default.job_port.saturating_add(offset)),
+                name,
+            });
+        }
+        check_profile_conflicts(&profiles)?;
+        Ok(profiles)
+    }
+
+    pub fn retry_policy(&self, step: &SetupStep) -> RetryPolicy {
+        self.setup.get(step)
+            .map(|setting| setting.value)
+            .unwrap_or_else(|| RetryPolicy::default_for(step))
+    }
+
+    // (name, value, source) rows for the configuration view
+    pub fn entries(&self) -> Vec<(&'static str, String, String)> {
+        let mut entries = vec![
+            ("metadata_url", self.metadata_url.value.clone(), self.metadata_url.source.to_string()),
+            ("image", self.image.value.clone(), self.image.source.to_string()),
+            ("container_name", self.container_name.value.clone(), self.container_name.source.to_string()),
+            ("sql_port", self.sql_port.value.to_string(), self.sql_port.source.to_string()),
+            ("job_port", self.job_port.value.to_string(), self.job_port.source.to_string()),
+            ("auto_ports", self.auto_ports.value.to_string(), self.auto_ports.source.to_string()),
+            ("table_sort", self.table_sort.value.to_string(), self.table_sort.source.to_string()),
+            ("demo", self.demo.value.to_string(), self.demo.source.to_string()),
+            ("profile", self.profile.value.clone(), self.profile.source.to_string()),
+            ("workspace", self.workspace.value.display().to_string(), self.workspace.source.to_string()),
+        ];
+        entries.extend(self.setup.iter().map(|(step, setting)| {
+            (step.config_key(), setting.value.to_string(), setting.source.to_string())
+        }));
+        entries
+    }
+}
+
+fn from_env<T: FromStr>(name: &'static str) -> Result<Option<T>, String> {
+    match env::var(name) {
+        Ok(value) if !value.is_empty() => value.parse()
+            .map(Some)
+            .map_err(|_| format!("Invalid value for {}: {}", name, value)),
+        _ => Ok(None),
+    }
+}
+
+// 容器名和宿主机端口在 profile 内和 profile 之间都不能重复，否则容器会因为端口已被占用而创建失败
+fn check_profile_conflicts(profiles: &[Profile]) -> Result<(), String> {
+    for (i, profile) in profiles.iter().enumerate() {
+        if profile.sql_port == profile.job_port {
+            return Err(format!(
+                "Profile '{}' uses port {} for both sql_port and job_port",
+                profile.name, profile.sql_port
+            ));
+        }
+        for other in &profiles[..i] {
+            if profile.container_name == other.container_name {
+                return Err(format!(
+                    "Profiles '{}' and '{}' both use container name '{}'",
+                    other.name, profile.name, profile.container_name
+                ));
+            }
+            let ports = [("sql_port", profile.sql_port), ("job_port", profile.job_port)];
+            let other_ports = [("sql_port", other.sql_port), ("job_port", other.job_port)];
+            for (key, port) in ports {
+                if let Some((other_key, _)) = other_ports.iter().find(|(_, other_port)| *other_port == port) {
+                    return Err(format!(
+                        "Profiles '{}' ({}) and '{}' ({}) both use port {}",
+                        other.name, other_key, profile.name, key, port
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use super::*;
+
+    // load 会读取 MS_* 环境变量，读写环境变量的测试需要串行执行
+    static ENV: Mutex<()> = Mutex::new(());
+
+    const ENV_VARS: &[&str] = &[
+        "MS_METADATA_URL", "MS_IMAGE", "MS_CONTAINER_NAME", "MS_SQL_PORT", "MS_JOB_PORT",
+        "MS_AUTO_PORTS", "MS_TABLE_SORT", "MS_DEMO", "MS_PROFILE", "MS_WORKSPACE",
+    ];
+
+    // 用给定的配置文件和环境变量加载配置
+    fn load(name: &str, file: &str, vars: &[(&str, &str)], overrides: Overrides) -> Result<Config, String> {
+        let _lock = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let path = env::temp_dir().join(format!("ms-config-test-{}-{}.toml", std::process::id(), name));
+        fs::write(&path, file).unwrap();
+        for var in ENV_VARS {
+            env::remove_var(var);
+        }
+        for (var, value) in vars {
+            env::set_var(var, value);
+        }
+        let config = Config::load(Some(path.clone()), overrides);
+        for (var, _) in vars {
+            env::remove_var(var);
+        }
+        fs::remove_file(&path).ok();
+        config
+    }
+
+    #[test]
+    fn file_then_env_then_cli_take_precedence() {
+        let file = "image = \"file-image\"\ncontainer_name = \"file-name\"\nsql_port = 20083\njob_port = 20081\n";
+        let vars = [("MS_CONTAINER_NAME", "env-name"), ("MS_SQL_PORT", "21083"), ("MS_JOB_PORT", "21081")];
+        let overrides = Overrides { job_port: Some(22081), ..Overrides::default() };
+        let config = load("layering", file, &vars, overrides).unwrap();
+
+        assert_eq!(config.metadata_url.value, NETWORK_CHAINS_URL);
+        assert_eq!(config.metadata_url.source, ConfigSource::Default);
+        assert_eq!(config.image.value, "file-image");
+        assert!(matches!(config.image.source, ConfigSource::File(_)));
+        assert_eq!(config.container_name.value, "env-name");
+        assert_eq!(config.container_name.source, ConfigSource::Env("MS_CONTAINER_NAME"));
+        assert_eq!(config.sql_port.value, 21083);
+        assert_eq!(config.sql_port.source, ConfigSource::Env("MS_SQL_PORT"));
+        assert_eq!(config.job_port.value, 22081);
+        assert_eq!(config.job_port.source, ConfigSource::Cli("--job-port"));
+    }
+
+    #[test]
+    fn invalid_env_value_is_an_error() {
+        let error = load("bad-env", "", &[("MS_SQL_PORT", "eighty")], Overrides::default()).unwrap_err();
+        assert_eq!(error, "Invalid value for MS_SQL_PORT: eighty");
+    }
+
+    #[test]
+    fn setup_policy_merges_file_values_over_defaults() {
+        let config = load("setup", "[setup.pull]\nretries = 5\nidle_timeout_secs = 30\n", &[], Overrides::default()).unwrap();
+        let pull = config.retry_policy(&SetupStep::PullingImage);
+        assert_eq!(pull.retries, 5);
+        assert_eq!(pull.idle_timeout, Some(Duration::from_secs(30)));
+        assert_eq!(pull.timeout, RetryPolicy::default_for(&SetupStep::PullingImage).timeout);
+        assert_eq!(config.retry_policy(&SetupStep::VerifyingSetup), RetryPolicy::default_for(&SetupStep::VerifyingSetup));
+    }
+
+    #[test]
+    fn profiles_derive_names_and_ports_from_the_default_profile() {
+        let file = "[profiles.staging]\n[profiles.local]\nsql_port = 30083\n";
+        let overrides = Overrides { profile: Some("local".to_string()), ..Overrides::default() };
+        let config = load("profiles", file, &[], overrides).unwrap();
+        let ports: Vec<(&str, &str, u16, u16)> = config.profiles.iter()
+            .map(|profile| (profile.name.as_str(), profile.container_name.as_str(), profile.sql_port, profile.job_port))
+            .collect();
+        assert_eq!(ports, vec![
+            ("default", "manuscript-debug", 18083, 18081),
+            ("staging", "manuscript-debug-staging", 18093, 18091),
+            ("local", "manuscript-debug-local", 30083, 18101),
+        ]);
+        assert_eq!(config.profile.value, "local");
+    }
+
+    #[test]
+    fn unknown_profile_lists_the_known_ones() {
+        let overrides = Overrides { profile: Some("prod".to_string()), ..Overrides::default() };
+        let error = load("unknown-profile", "[profiles.staging]\n", &[], overrides).unwrap_err();
+        assert_eq!(error, "Unknown profile 'prod' (flag --profile), expected one of: default, staging");
+    }
+
+    #[test]
+    fn same_port_for_sql_and_job_is_rejected() {
+        let overrides = Overrides { job_port: Some(18083), ..Overrides::default() };
+        let error = load("same-port-cli", "", &[], overrides).unwrap_err();
+        assert_eq!(error, "Profile 'default' uses port 18083 for both sql_port and job_port");
+
+        let error = load("same-port-file", "[profiles.a]\nsql_port = 30000\njob_port = 30000\n", &[], Overrides::default()).unwrap_err();
+        assert_eq!(error, "Profile 'a' uses port 30000 for both sql_port and job_port");
+    }
+
+    #[test]
+    fn profiles_sharing_a_port_or_container_name_are_rejected() {
+        let error = load("shared-port", "[profiles.a]\njob_port = 18083\n", &[], Overrides::default()).unwrap_err();
+        assert_eq!(error, "Profiles 'default' (sql_port) and 'a' (job_port) both use port 18083");
+
+        let file = "[profiles.a]\ncontainer_name = \"x\"\n[profiles.b]\ncontainer_name = \"x\"\n";
+        let error = load("shared-name", file, &[], Overrides::default()).unwrap_err();
+        assert_eq!(error, "Profiles 'a' and 'b' both use container name 'x'");
+    }
+
+    #[test]
+    fn env_port_can_collide_with_a_profile() {
+        let vars = [("MS_SQL_PORT", "30000")];
+        let error = load("env-collision", "[profiles.a]\njob_port = 30000\n", &vars, Overrides::default()).unwrap_err();
+        assert_eq!(error, "Profiles 'default' (sql_port) and 'a' (job_port) both use port 30000");
+    }
+}
@@ -1,130 +1,724 @@
-use std::process::Command;
-use std::time::Duration;
-use tokio::time::sleep;
-use tokio::sync::mpsc;
+use std::{collections::BTreeMap, fmt, future::Future, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, time::{Duration, Instant}};
+use chrono::{DateTime, Utc};
+use tokio::time::{sleep, timeout};
+use tokio::sync::{mpsc, Notify};
 use crate::app::AppUpdate;
 use crate::app::SetupStep;
 use crate::app::SetupStepStatus;
+use crate::config::{Config, Profile, RetryPolicy};
+use crate::ports::{self, Ports};
+use crate::pull::PullProgress;
+use crate::engine::{ContainerInspect, ContainerSpec, DockerHost, EngineClient, RegistryAuth};
 
-#[derive(Debug)]
+// 容器面板上可执行的操作，执行前都需要确认
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerAction {
+    Stop,
+    Restart,
+    Remove,
+    Recreate,
+}
+
+impl fmt::Display for ContainerAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerAction::Stop => write!(f, "stop"),
+            ContainerAction::Restart => write!(f, "restart"),
+            ContainerAction::Remove => write!(f, "remove"),
+            ContainerAction::Recreate => write!(f, "recreate"),
+        }
+    }
+}
+
+impl ContainerAction {
+    pub fn past_tense(&self) -> &'static str {
+        match self {
+            ContainerAction::Stop => "stopped",
+            ContainerAction::Restart => "restarted",
+            ContainerAction::Remove => "removed",
+            ContainerAction::Recreate => "recreated",
+        }
+    }
+}
+
+// 容器面板显示的状态
+#[derive(Debug, Clone)]
+pub struct ContainerInfo {
+    pub id: String,
+    pub image: String,
+    pub status: String,
+    pub running: bool,
+    pub exit_code: i64,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub ports: Vec<(String, String)>,  // (容器端口, 宿主机地址)
+}
+
+impl From<ContainerInspect> for ContainerInfo {
+    fn from(container: ContainerInspect) -> Self {
+        let parse_time = |time: &str| DateTime::parse_from_rfc3339(time)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+            // 从未启动/停止过的容器时间为 0001-01-01
+            .filter(|time| time.timestamp() > 0);
+
+        let mut ports: Vec<(String, String)> = container.network_settings.ports
+            .into_iter()
+            .flat_map(|(port, bindings)| {
+                bindings.unwrap_or_default().into_iter().map(move |binding| {
+                    let host_ip = if binding.host_ip.is_empty() { "0.0.0.0" } else { binding.host_ip.as_str() };
+                    (port.clone(), format!("{}:{}", host_ip, binding.host_port))
+                })
+            })
+            .collect();
+        ports.sort();
+
+        Self {
+            id: container.id,
+            image: container.config.image,
+            status: container.state.status,
+            running: container.state.running,
+            exit_code: container.state.exit_code,
+            started_at: parse_time(&container.state.started_at),
+            finished_at: parse_time(&container.state.finished_at),
+            ports,
+        }
+    }
+}
+
+impl ContainerInfo {
+    pub fn uptime(&self) -> Option<String> {
+        let started_at = self.started_at.filter(|_| self.running)?;
+        let seconds = Utc::now().signed_duration_since(started_at).num_seconds().max(0);
+        Some(format!("{}h {:02}m {:02}s", seconds / 3600, seconds % 3600 / 60, seconds % 60))
+    }
+}
+
+const LOG_TAIL: usize = 500;
+const SQL_CONTAINER_PORT: u16 = 8083;
+const JOB_CONTAINER_PORT: u16 = 8081;
+const VERIFY_INTERVAL: Duration = Duration::from_secs(1);
+const VERIFY_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+// 取消时 create 请求可能还在 daemon 里执行，按名字查找几次
+const CLEANUP_LOOKUPS: u32 = 10;
+const CLEANUP_INTERVAL: Duration = Duration::from_millis(500);
+
+// setup 失败时的步骤和原因，或者被用户取消
+#[derive(Debug, Clone)]
+pub enum SetupError {
+    Failed { step: SetupStep, message: String },
+    Cancelled(String),
+}
+
+impl SetupError {
+    fn new(step: SetupStep, message: String) -> Self {
+        SetupError::Failed { step, message }
+    }
+
+    fn at(step: SetupStep) -> impl FnOnce(String) -> Self {
+        move |message| Self::new(step, message)
+    }
+}
+
+// 本次 setup 创建的容器，取消时据此清理
+#[derive(Debug, Clone, Default)]
+enum Created {
+    #[default]
+    Nothing,
+    Requested,  // create 请求已发出但还没有返回 id；此前不存在同名容器
+    Container(String),
+}
+
+// setup 步骤中一次失败的尝试
+#[derive(Debug, Clone)]
+pub struct SetupAttempt {
+    pub number: u32,
+    pub attempts: u32,
+    pub error: String,
+    pub retry_in: Option<Duration>,
+}
+
+// UI 和后台 setup 任务共享的取消信号
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle {
+    inner: Arc<CancelInner>,
+}
+
+#[derive(Debug, Default)]
+struct CancelInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub async fn cancelled(&self) {
+        // 先注册再检查标志，避免错过 cancel() 的通知
+        let notified = self.inner.notify.notified();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+async fn report(sender: Option<&mpsc::Sender<AppUpdate>>, step: SetupStep, status: SetupStepStatus) {
+    if let Some(sender) = sender {
+        let _ = sender.send(AppUpdate::SetupProgress(step, status)).await;
+    }
+}
+
+// reqwest 的错误本身只有 "error sending request"，原因在 source 链里
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
+}
+
+#[derive(Debug, Clone)]
 pub struct DockerManager {
+    engine: Result<EngineClient, String>,
+    profile: String,
     image: String,
+    container_name: String,
+    ports: Arc<Mutex<Ports>>,  // UI 和后台任务共享，自动选端口后双方都能看到
+    auto_ports: bool,
+    retry_policies: BTreeMap<SetupStep, RetryPolicy>,
 }
 
 impl DockerManager {
-    pub fn new() -> Self {
+    pub fn new(profile: &Profile, config: &Config) -> Self {
         Self {
-            image: "repository.chainbase.com/manuscript-node/manuscript-debug:v0.0.1".to_string(),
+            engine: DockerHost::from_env()
+                .map(EngineClient::new)
+                .map_err(|e| e.to_string()),
+            profile: profile.name.clone(),
+            image: profile.image.clone(),
+            container_name: profile.container_name.clone(),
+            ports: Arc::new(Mutex::new(Ports {
+                sql: profile.sql_port,
+                job: profile.job_port,
+            })),
+            auto_ports: config.auto_ports.value,
+            retry_policies: SetupStep::ALL.iter()
+                .map(|step| (step.clone(), config.retry_policy(step)))
+                .collect(),
         }
     }
 
-    pub async fn setup(&self, sender: Option<mpsc::Sender<AppUpdate>>) -> Result<String, String> {
-        // Step 1: Check Docker installation
-        if let Some(sender) = &sender {
-            let _ = sender.send(AppUpdate::SetupProgress(SetupStep::CheckingDocker, SetupStepStatus::InProgress)).await;
-        }
-        if !self.check_docker_installed() {
-            return Err("Docker is not installed or not accessible".to_string());
+    // 取消时丢弃正在进行的 API 请求，并删除本次 setup 创建的容器
+    pub async fn setup(&self, sender: Option<mpsc::Sender<AppUpdate>>, cancel: &CancelHandle) -> Result<String, SetupError> {
+        let created = Mutex::new(Created::Nothing);
+        let result = tokio::select! {
+            result = self.run_setup(sender.as_ref(), &created) => result,
+            _ = cancel.cancelled() => Err(SetupError::Cancelled("Setup cancelled".to_string())),
+        };
+        if !matches!(result, Err(SetupError::Cancelled(_))) {
+            return result;
         }
 
-        // Step 2: Pull the image
-        if let Some(sender) = &sender {
-            let _ = sender.send(AppUpdate::SetupProgress(SetupStep::PullingImage, SetupStepStatus::InProgress)).await;
+        let created = std::mem::take(&mut *created.lock().unwrap());
+        let (id, engine) = match (created, self.engine()) {
+            (Created::Container(id), Ok(engine)) => (Some(id), engine),
+            (Created::Requested, Ok(engine)) => (self.find_requested_container(engine).await, engine),
+            _ => return Err(SetupError::Cancelled("Setup cancelled".to_string())),
+        };
+        let message = match id {
+            Some(id) => match engine.remove_container(&id).await {
+                Ok(()) => format!("Setup cancelled, removed container {}", self.container_name),
+                Err(e) => format!("Setup cancelled, but failed to remove container {}: {}", self.container_name, e),
+            },
+            None => "Setup cancelled".to_string(),
+        };
+        Err(SetupError::Cancelled(message))
+    }
+
+    // create 请求被取消时不知道 id，daemon 可能已经创建了容器
+    async fn find_requested_container(&self, engine: &EngineClient) -> Option<String> {
+        for lookup in 0..CLEANUP_LOOKUPS {
+            if lookup > 0 {
+                sleep(CLEANUP_INTERVAL).await;
+            }
+            match engine.find_container(&self.container_name).await {
+                Ok(Some(container)) => return Some(container.id),
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to look up container {}: {}", self.container_name, e),
+            }
         }
-        if let Err(e) = self.pull_image().await {
-            return Err(format!("Failed to pull image: {}", e));
+        None
+    }
+
+    async fn run_setup(&self, sender: Option<&mpsc::Sender<AppUpdate>>, created: &Mutex<Created>) -> Result<String, SetupError> {
+        // Step 1: Check Docker installation
+        let step = SetupStep::CheckingDocker;
+        report(sender, step.clone(), SetupStepStatus::InProgress).await;
+        let engine = self.engine().map_err(SetupError::at(step.clone()))?;
+        let version = self.retry(&step, sender, step.as_str(), || async {
+            engine.version().await.map_err(|e| e.to_string())
+        })
+            .await
+            .map_err(|e| SetupError::new(step.clone(), format!("Docker is not accessible: {}", e)))?;
+        if let Some(sender) = sender {
+            let _ = sender.send(AppUpdate::DockerStatus(format!(
+                "Docker {} (API {})", version.version, version.api_version
+            ))).await;
         }
+        report(sender, step, SetupStepStatus::Complete).await;
+
+        // Step 2: Pull the image
+        let step = SetupStep::PullingImage;
+        report(sender, step.clone(), SetupStepStatus::InProgress).await;
+        self.retry(&step, sender, step.as_str(), || async {
+            self.pull_image(engine, sender).await
+        })
+            .await
+            .map_err(|e| SetupError::new(step.clone(), format!("Failed to pull image: {}", e)))?;
+        report(sender, step, SetupStepStatus::Complete).await;
 
         // Step 3: Run the container
-        if let Some(sender) = &sender {
-            let _ = sender.send(AppUpdate::SetupProgress(SetupStep::StartingContainer, SetupStepStatus::InProgress)).await;
-        }
-        if let Err(e) = self.run_container().await {
-            return Err(format!("Failed to start container: {}", e));
-        }
+        let step = SetupStep::StartingContainer;
+        report(sender, step.clone(), SetupStepStatus::InProgress).await;
+        self.retry(&step, sender, step.as_str(), || async {
+            self.run_container(engine, created, sender).await
+        })
+            .await
+            .map_err(|e| SetupError::new(step.clone(), format!("Failed to start container: {}", e)))?;
+        report(sender, step, SetupStepStatus::Complete).await;
 
         // Step 4: Configure network
-        if let Some(sender) = &sender {
-            let _ = sender.send(AppUpdate::SetupProgress(SetupStep::ConfiguringNetwork, SetupStepStatus::InProgress)).await;
-        }
+        let step = SetupStep::ConfiguringNetwork;
+        report(sender, step.clone(), SetupStepStatus::InProgress).await;
+        self.retry(&step, sender, step.as_str(), || self.adopt_port_bindings(engine, sender))
+            .await
+            .map_err(SetupError::at(step.clone()))?;
+        report(sender, step, SetupStepStatus::Complete).await;
 
         // Step 5: Verify setup
-        if let Some(sender) = &sender {
-            let _ = sender.send(AppUpdate::SetupProgress(SetupStep::VerifyingSetup, SetupStepStatus::InProgress)).await;
-        }
+        let step = SetupStep::VerifyingSetup;
+        report(sender, step.clone(), SetupStepStatus::InProgress).await;
+        self.verify_endpoints(engine, sender).await.map_err(SetupError::at(step.clone()))?;
+        report(sender, step, SetupStepStatus::Complete).await;
 
         Ok("Container started successfully".to_string())
     }
 
-    fn check_docker_installed(&self) -> bool {
-        Command::new("docker")
-            .arg("--version")
-            .output()
-            .is_ok()
+    // 依次等待 SQL 和 job manager 端口可以访问
+    async fn verify_endpoints(&self, engine: &EngineClient, sender: Option<&mpsc::Sender<AppUpdate>>) -> Result<(), String> {
+        let step = SetupStep::VerifyingSetup;
+        for (name, port) in self.endpoints() {
+            let url = format!("http://127.0.0.1:{}/", port);
+            let what = format!("{} at {}", name, url);
+            let last_error = Mutex::new(None);
+            self.retry_probing(&step, sender, &what, &last_error, || self.wait_for_endpoint(engine, &url, &last_error))
+                .await?;
+        }
+        Ok(())
     }
 
-    async fn pull_image(&self) -> Result<(), String> {
-        let output = Command::new("docker")
-            .args(["pull", &self.image])
-            .output()
-            .map_err(|e| e.to_string())?;
+    // 按步骤的策略执行：每次尝试都有超时，失败后按指数退避重试，并把每次失败报告给 UI
+    async fn retry<T, F, Fut>(&self, step: &SetupStep, sender: Option<&mpsc::Sender<AppUpdate>>, what: &str, attempt: F) -> Result<T, String>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        self.retry_probing(step, sender, what, &Mutex::new(None), attempt).await
+    }
 
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(String::from_utf8_lossy(&output.stderr).to_string())
+    // 同 retry；尝试超时时附上 last_error 中记录的最后一次失败原因
+    async fn retry_probing<T, F, Fut>(
+        &self,
+        step: &SetupStep,
+        sender: Option<&mpsc::Sender<AppUpdate>>,
+        what: &str,
+        last_error: &Mutex<Option<String>>,
+        mut attempt: F,
+    ) -> Result<T, String>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let policy = self.retry_policies[step];
+        let attempts = policy.retries + 1;
+        let mut number = 1;
+        loop {
+            let error = match timeout(policy.timeout, attempt()).await {
+                Ok(Ok(value)) => return Ok(value),
+                Ok(Err(e)) => e,
+                Err(_) => {
+                    let timed_out = format!("{} timed out after {}s", what, policy.timeout.as_secs());
+                    match last_error.lock().unwrap().take() {
+                        Some(last) => format!("{}: {}", timed_out, last),
+                        None => timed_out,
+                    }
+                }
+            };
+            let retry_in = (number < attempts).then(|| policy.delay(number));
+            if let Some(sender) = sender {
+                let _ = sender.send(AppUpdate::SetupAttemptFailed(step.clone(), SetupAttempt {
+                    number,
+                    attempts,
+                    error: error.clone(),
+                    retry_in,
+                })).await;
+            }
+            match retry_in {
+                Some(delay) => sleep(delay).await,
+                None => return Err(error),
+            }
+            number += 1;
         }
     }
 
-    async fn run_container(&self) -> Result<(), String> {
-        // Check if container already exists
-        let check_output = Command::new("docker")
-            .args(["ps", "-q", "-f", "name=manuscript-debug"])
-            .output()
-            .map_err(|e| e.to_string())?;
+    // 当前使用的宿主机端口，可能是自动选择的或从已有容器读取的
+    pub fn ports(&self) -> Ports {
+        *self.ports.lock().unwrap()
+    }
+
+    fn set_ports(&self, ports: Ports) {
+        *self.ports.lock().unwrap() = ports;
+    }
+
+    pub fn set_auto_ports(&mut self, auto_ports: bool) {
+        self.auto_ports = auto_ports;
+    }
+
+    pub fn sql_endpoint(&self) -> String {
+        format!("http://127.0.0.1:{}", self.ports().sql)
+    }
+
+    pub fn job_endpoint(&self) -> String {
+        format!("http://127.0.0.1:{}", self.ports().job)
+    }
+
+    // 容器内的服务及其映射到宿主机的端口
+    fn endpoints(&self) -> [(&'static str, u16); 2] {
+        let ports = self.ports();
+        [("SQL endpoint", ports.sql), ("Job manager", ports.job)]
+    }
 
-        // If container exists (output not empty), return success
-        if !String::from_utf8_lossy(&check_output.stdout).trim().is_empty() {
+    fn port_mappings(&self) -> [(u16, u16); 2] {
+        let ports = self.ports();
+        [(SQL_CONTAINER_PORT, ports.sql), (JOB_CONTAINER_PORT, ports.job)]
+    }
+
+    // 新建容器前检查端口，冲突时按配置自动换用空闲端口
+    async fn prepare_ports(&self, sender: Option<&mpsc::Sender<AppUpdate>>) -> Result<(), String> {
+        let ports = self.ports();
+        let conflicts = ports::conflicts(ports);
+        if conflicts.is_empty() {
             return Ok(());
         }
+        if !self.auto_ports {
+            let message = conflicts.iter().map(|conflict| conflict.to_string()).collect::<Vec<_>>().join(", ");
+            if let Some(sender) = sender {
+                let _ = sender.send(AppUpdate::PortsInUse(conflicts)).await;
+            }
+            return Err(format!("Cannot publish container ports: {}", message));
+        }
 
-        // Container doesn't exist, create and run it
-        let output = Command::new("docker")
-            .args([
-                "run",
-                "-d",  // Run in detached mode
-                "--rm",
-                "--name",
-                "manuscript-debug", 
-                "-p", "18083:8083",
-                "-p", "18081:8081",
-                &self.image,
-            ])
-            .output()
-            .map_err(|e| e.to_string())?;
+        let free = ports::pick_free(ports).map_err(|e| format!("Failed to find free ports: {}", e))?;
+        self.set_ports(free);
+        if let Some(sender) = sender {
+            let _ = sender.send(AppUpdate::PortsChanged(free)).await;
+        }
+        Ok(())
+    }
 
-        if !output.status.success() {
-            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    // 确认容器在运行，并采用它实际映射到宿主机的端口（可能来自之前自动选择的端口）
+    async fn adopt_port_bindings(&self, engine: &EngineClient, sender: Option<&mpsc::Sender<AppUpdate>>) -> Result<(), String> {
+        let container = engine.inspect_container(&self.container_name)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Container {} disappeared after starting", self.container_name))?;
+        if !container.state.running {
+            return Err(format!("Container exited with code {}", container.state.exit_code));
         }
 
-        // Wait for container to be ready
-        sleep(Duration::from_secs(5)).await;
+        let host_port = |container_port: u16| {
+            let key = format!("{}/tcp", container_port);
+            container.network_settings.ports
+                .get(&key)
+                .and_then(|bindings| bindings.as_ref())
+                .and_then(|bindings| bindings.iter().find_map(|binding| binding.host_port.parse::<u16>().ok()))
+                .ok_or_else(|| format!(
+                    "Port {} is not published to the host; recreate the container from the container panel (d)",
+                    key
+                ))
+        };
+        let ports = Ports {
+            sql: host_port(SQL_CONTAINER_PORT)?,
+            job: host_port(JOB_CONTAINER_PORT)?,
+        };
+        if ports != self.ports() {
+            self.set_ports(ports);
+            if let Some(sender) = sender {
+                let _ = sender.send(AppUpdate::PortsChanged(ports)).await;
+            }
+        }
         Ok(())
     }
 
-    pub fn get_setup_progress(&self, timer: u64) -> Vec<(String, bool)> {
-        vec![
-            ("Checking Docker installation".to_string(), timer >= 5),
-            ("Pulling required images".to_string(), timer >= 25),
-            ("Starting container".to_string(), timer >= 45),
-            ("Configuring network".to_string(), timer >= 65),
-            ("Verifying setup".to_string(), timer >= 85)
-        ]
+    // 轮询服务直到有 HTTP 响应（任意状态码都说明服务已经在监听）
+    // 超时由步骤的重试策略控制
+    async fn wait_for_endpoint(&self, engine: &EngineClient, url: &str, last_error: &Mutex<Option<String>>) -> Result<(), String> {
+        let client = reqwest::Client::builder()
+            .timeout(VERIFY_REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        loop {
+            match client.get(url).send().await {
+                Ok(_) => return Ok(()),
+                Err(e) => *last_error.lock().unwrap() = Some(error_chain(&e)),
+            }
+
+            // 服务没起来时先确认容器还活着，避免一直等到超时
+            let container = engine.inspect_container(&self.container_name)
+                .await
+                .map_err(|e| e.to_string())?;
+            match container {
+                Some(container) if container.state.running => {}
+                Some(container) => {
+                    return Err(format!(
+                        "Container exited with code {} while waiting for {}",
+                        container.state.exit_code, url
+                    ));
+                }
+                None => return Err(format!("Container {} was removed while waiting for {}", self.container_name, url)),
+            }
+            sleep(VERIFY_INTERVAL).await;
+        }
+    }
+
+    fn engine(&self) -> Result<&EngineClient, String> {
+        self.engine.as_ref().map_err(|e| e.clone())
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    pub fn image(&self) -> &str {
+        &self.image
+    }
+
+    pub fn container_name(&self) -> &str {
+        &self.container_name
+    }
+
+    // 查询容器当前状态，容器不存在时返回 None
+    pub async fn container_info(&self) -> Result<Option<ContainerInfo>, String> {
+        self.engine()?
+            .inspect_container(&self.container_name)
+            .await
+            .map(|container| container.map(ContainerInfo::from))
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn apply(&self, action: ContainerAction) -> Result<(), String> {
+        let engine = self.engine()?;
+        let container = engine.inspect_container(&self.container_name)
+            .await
+            .map_err(|e| e.to_string())?;
+        let result = match (action, container) {
+            (ContainerAction::Recreate, Some(container)) => {
+                match engine.remove_container(&container.id).await {
+                    Ok(()) => self.run_container(engine, &Mutex::default(), None).await,
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+            (ContainerAction::Recreate, None) => self.run_container(engine, &Mutex::default(), None).await,
+            (_, None) => return Err(format!("Container {} does not exist", self.container_name)),
+            (ContainerAction::Stop, Some(container)) => engine.stop_container(&container.id).await.map_err(|e| e.to_string()),
+            (ContainerAction::Restart, Some(container)) => engine.restart_container(&container.id).await.map_err(|e| e.to_string()),
+            (ContainerAction::Remove, Some(container)) => engine.remove_container(&container.id).await.map_err(|e| e.to_string()),
+        };
+        result.map_err(|e| format!("Failed to {} container: {}", action, e))
+    }
+
+    // 持续把容器日志发送给 UI，直到流结束或 UI 退出
+    pub async fn follow_logs(&self, sender: mpsc::Sender<AppUpdate>) {
+        let result = async {
+            let engine = self.engine()?;
+            let mut stream = engine.follow_logs(&self.container_name, LOG_TAIL)
+                .await
+                .map_err(|e| e.to_string())?;
+            while let Some(lines) = stream.next_lines().await.map_err(|e| e.to_string())? {
+                if sender.send(AppUpdate::LogLines(self.container_name.clone(), lines)).await.is_err() {
+                    break;
+                }
+            }
+            Ok::<(), String>(())
+        }.await;
+        let _ = sender.send(AppUpdate::LogsEnded(self.container_name.clone(), result.err())).await;
+    }
+
+    // 通过 Engine API 拉取镜像，把逐层进度发送给 UI；超过 idle_timeout 没有进度事件时放弃本次尝试
+    async fn pull_image(&self, engine: &EngineClient, sender: Option<&mpsc::Sender<AppUpdate>>) -> Result<(), String> {
+        let mut progress = PullProgress::default();
+        let auth = RegistryAuth::for_image(&self.image).await.map_err(|e| e.to_string())?;
+        let last_event = Mutex::new(Instant::now());
+        let pull = engine.pull_image(&self.image, auth.as_ref(), |event| {
+            *last_event.lock().unwrap() = Instant::now();
+            let Some(id) = &event.id else {
+                return;
+            };
+            if progress.update(id, &event.status, event.progress_detail.current, event.progress_detail.total) {
+                if let Some(sender) = sender {
+                    // 进度事件很密集，通道满时丢弃中间状态
+                    let _ = sender.try_send(AppUpdate::PullProgress(progress.clone()));
+                }
+            }
+        });
+        let Some(idle_timeout) = self.retry_policies[&SetupStep::PullingImage].idle_timeout else {
+            return pull.await.map_err(|e| e.to_string());
+        };
+        let stalled = async {
+            loop {
+                let idle = last_event.lock().unwrap().elapsed();
+                if idle >= idle_timeout {
+                    return;
+                }
+                sleep(idle_timeout - idle).await;
+            }
+        };
+        tokio::select! {
+            result = pull => result.map_err(|e| e.to_string()),
+            _ = stalled => Err(format!("no progress from the registry for {}s", idle_timeout.as_secs())),
+        }
+    }
+
+    // 发出 create 请求前先记录到 created 中，以便请求途中被取消时也能清理
+    async fn run_container(
+        &self,
+        engine: &EngineClient,
+        created: &Mutex<Created>,
+        sender: Option<&mpsc::Sender<AppUpdate>>,
+    ) -> Result<(), String> {
+        // Reuse the container if it is already running
+        let existing = engine.find_container(&self.container_name).await.map_err(|e| e.to_string())?;
+        match existing {
+            Some(container) if container.state == "running" => Ok(()),
+            Some(container) => engine.start_container(&container.id).await.map_err(|e| e.to_string()),
+            None => {
+                self.prepare_ports(sender).await?;
+                let spec = ContainerSpec::new(&self.image, &self.port_mappings());
+                *created.lock().unwrap() = Created::Requested;
+                let id = engine.create_container(&self.container_name, &spec).await.map_err(|e| e.to_string())?;
+                *created.lock().unwrap() = Created::Container(id.clone());
+                engine.start_container(&id).await.map_err(|e| e.to_string())
+            }
+        }
     }
 }
 
-impl Clone for DockerManager {
-    fn clone(&self) -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        os::unix::net::UnixListener,
+        path::PathBuf,
+        thread,
+    };
+    use super::*;
+
+    // 读完请求头后回复固定的响应，每个连接一个请求
+    fn respond<S: std::io::Read + Write>(mut stream: S, reader: S, body: &str) {
+        let mut reader = BufReader::new(reader);
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                break;
+            }
+        }
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(), body
+        );
+    }
+
+    fn http_endpoint() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let reader = stream.try_clone().unwrap();
+                respond(stream, reader, "{}");
+            }
+        });
+        port
+    }
+
+    // 绑定后立即释放的端口，连接会被拒绝
+    fn closed_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    }
+
+    // 假的 Docker daemon：容器一直在运行
+    fn running_engine(name: &str) -> EngineClient {
+        let dir = std::env::temp_dir().join(format!("ms-docker-test-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("docker.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let reader = stream.try_clone().unwrap();
+                respond(stream, reader, r#"{"Id": "c1", "State": {"Status": "running", "Running": true},
+                    "Config": {"Image": "debug"}, "NetworkSettings": {"Ports": {}}}"#);
+            }
+        });
+        EngineClient::new(DockerHost::Unix(path))
+    }
+
+    fn manager(sql_port: u16, job_port: u16, verify_timeout: Duration) -> DockerManager {
+        let profile = Profile {
+            name: "test".to_string(),
+            image: "debug".to_string(),
+            container_name: "ms-test".to_string(),
+            sql_port,
+            job_port,
+        };
+        let mut manager = DockerManager::new(&profile, &Config::default());
+        manager.retry_policies.insert(SetupStep::VerifyingSetup, RetryPolicy {
+            timeout: verify_timeout,
+            retries: 0,
+            backoff: Duration::ZERO,
+            idle_timeout: None,
+        });
+        manager
+    }
+
+    #[tokio::test]
+    async fn verify_succeeds_when_both_endpoints_answer() {
+        let manager = manager(http_endpoint(), http_endpoint(), Duration::from_secs(5));
+        // 端口可以访问时不会查询容器状态
+        let engine = EngineClient::new(DockerHost::Unix(PathBuf::from("/nonexistent/docker.sock")));
+        assert_eq!(manager.verify_endpoints(&engine, None).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn verify_timeout_reports_why_the_endpoint_is_unreachable() {
+        let port = closed_port();
+        let manager = manager(port, http_endpoint(), Duration::from_secs(2));
+        let engine = running_engine("refused");
+        let error = manager.verify_endpoints(&engine, None).await.unwrap_err();
+        let expected = format!("SQL endpoint at http://127.0.0.1:{}/ timed out after 2s: ", port);
+        assert!(error.starts_with(&expected), "{}", error);
+        assert!(error.contains("Connection refused"), "{}", error);
     }
-} 
\ No newline at end of file
+}
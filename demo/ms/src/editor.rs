@@ -0,0 +1,112 @@
+use std::ops::Range;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// 多行文本编辑：SQL 编辑器和 manuscript 编辑器共用，cursor 为字节位置
+#[derive(Debug, Clone, Default)]
+pub struct TextEditor {
+    pub text: String,
+    pub cursor: usize,
+}
+
+impl TextEditor {
+    // 光标放在末尾
+    pub fn new(text: String) -> Self {
+        let cursor = text.len();
+        Self { text, cursor }
+    }
+
+    // 处理光标移动和输入；其他按键返回 false 由调用方处理
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> bool {
+        match key_event.code {
+            KeyCode::Enter => self.insert('\n'),
+            KeyCode::Tab => self.insert_str("  "),
+            // Ctrl 组合键留给调用方
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Up => self.up(),
+            KeyCode::Down => self.down(),
+            KeyCode::Home => self.cursor = self.line_start(),
+            KeyCode::End => self.cursor = self.line_end(),
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+    }
+
+    // 用 s 替换 range，光标移到替换后的末尾
+    pub fn replace(&mut self, range: Range<usize>, s: &str) {
+        self.cursor = range.start + s.len();
+        self.text.replace_range(range, s);
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    fn line_start(&self) -> usize {
+        self.text[..self.cursor].rfind('\n').map(|pos| pos + 1).unwrap_or(0)
+    }
+
+    fn line_end(&self) -> usize {
+        self.text[self.cursor..].find('\n').map(|pos| pos + self.cursor).unwrap_or(self.text.len())
+    }
+
+    // 移到 start 开始的那一行的第 column 列（按字符计），超出行尾时停在行尾
+    fn move_to_column(&mut self, start: usize, column: usize) {
+        let line = &self.text[start..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        self.cursor = start + line.char_indices().nth(column).map(|(i, _)| i).unwrap_or(line.len());
+    }
+
+    fn column(&self) -> usize {
+        self.text[self.line_start()..self.cursor].chars().count()
+    }
+
+    pub fn up(&mut self) {
+        let start = self.line_start();
+        if start > 0 {
+            let column = self.column();
+            let previous = self.text[..start - 1].rfind('\n').map(|pos| pos + 1).unwrap_or(0);
+            self.move_to_column(previous, column);
+        }
+    }
+
+    pub fn down(&mut self) {
+        let end = self.line_end();
+        if end < self.text.len() {
+            let column = self.column();
+            self.move_to_column(end + 1, column);
+        }
+    }
+
+    // 光标所在的行号（从 0 开始）
+    pub fn cursor_line(&self) -> usize {
+        self.text[..self.cursor].matches('\n').count()
+    }
+}
@@ -0,0 +1,604 @@
+use std::{collections::HashMap, env, fmt, path::{Path, PathBuf}, process::Stdio};
+use base64::{engine::general_purpose::{STANDARD, URL_SAFE}, Engine};
+use bytes::{Bytes, BytesMut};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Incoming, client::conn::http1, header, Method, Request, Response};
+use hyper_util::rt::TokioIo;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use tokio::{io::AsyncWriteExt, net::{TcpStream, UnixStream}, process::Command};
+
+pub const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+// Docker Hub 在 config.json 中的键
+const DOCKER_HUB: &str = "https://index.docker.io/v1/";
+
+// Docker Engine API 的连接地址，来自 DOCKER_HOST 或默认的 unix socket
+#[derive(Debug, Clone, PartialEq)]
+pub enum DockerHost {
+    Unix(PathBuf),
+    Tcp(String),
+}
+
+impl DockerHost {
+    pub fn from_env() -> Result<Self, EngineError> {
+        match env::var("DOCKER_HOST") {
+            Ok(host) if !host.is_empty() => Self::parse(&host),
+            _ => Ok(DockerHost::Unix(PathBuf::from(DEFAULT_SOCKET))),
+        }
+    }
+
+    pub fn parse(host: &str) -> Result<Self, EngineError> {
+        if let Some(path) = host.strip_prefix("unix://") {
+            Ok(DockerHost::Unix(PathBuf::from(path)))
+        } else if let Some(address) = host.strip_prefix("tcp://") {
+            Ok(DockerHost::Tcp(address.trim_end_matches('/').to_string()))
+        } else {
+            Err(EngineError::UnsupportedHost(host.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for DockerHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DockerHost::Unix(path) => write!(f, "unix://{}", path.display()),
+            DockerHost::Tcp(address) => write!(f, "tcp://{}", address),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EngineError {
+    UnsupportedHost(String),
+    Connect(String, std::io::Error),
+    Http(hyper::Error),
+    Api { status: u16, message: String },
+    Decode(serde_json::Error),
+    Credentials(String),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::UnsupportedHost(host) => write!(f, "Unsupported DOCKER_HOST: {}", host),
+            EngineError::Connect(host, e) => write!(f, "Cannot connect to the Docker daemon at {}: {}", host, e),
+            EngineError::Http(e) => write!(f, "Docker API request failed: {}", e),
+            EngineError::Api { status, message } => write!(f, "Docker API error ({}): {}", status, message),
+            EngineError::Decode(e) => write!(f, "Unexpected Docker API response: {}", e),
+            EngineError::Credentials(message) => write!(f, "Cannot read registry credentials: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<hyper::Error> for EngineError {
+    fn from(e: hyper::Error) -> Self {
+        EngineError::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for EngineError {
+    fn from(e: serde_json::Error) -> Self {
+        EngineError::Decode(e)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Version {
+    pub version: String,
+    pub api_version: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerSummary {
+    pub id: String,
+    #[serde(default)]
+    pub state: String,
+}
+
+// GET /containers/{id}/json 中用到的部分
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerInspect {
+    pub id: String,
+    pub state: ContainerState,
+    pub config: ContainerConfig,
+    pub network_settings: NetworkSettings,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerState {
+    pub status: String,
+    pub running: bool,
+    #[serde(default)]
+    pub exit_code: i64,
+    #[serde(default)]
+    pub started_at: String,
+    #[serde(default)]
+    pub finished_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerConfig {
+    pub image: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NetworkSettings {
+    // "8083/tcp" → 宿主机绑定；容器未运行时为 null
+    #[serde(default)]
+    pub ports: HashMap<String, Option<Vec<PortBinding>>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressDetail {
+    pub current: Option<u64>,
+    pub total: Option<u64>,
+}
+
+// /images/create 返回的 JSON 流中的一条事件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullEvent {
+    #[serde(default)]
+    pub status: String,
+    pub id: Option<String>,
+    #[serde(default)]
+    pub progress_detail: ProgressDetail,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerSpec {
+    pub image: String,
+    pub exposed_ports: HashMap<String, serde_json::Value>,
+    pub host_config: HostConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HostConfig {
+    pub port_bindings: HashMap<String, Vec<PortBinding>>,
+    pub auto_remove: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PortBinding {
+    pub host_ip: String,
+    pub host_port: String,
+}
+
+impl ContainerSpec {
+    // (container port, host port) pairs, all tcp
+    pub fn new(image: &str, ports: &[(u16, u16)]) -> Self {
+        let exposed_ports = ports.iter()
+            .map(|(container, _)| (format!("{}/tcp", container), json!({})))
+            .collect();
+        let port_bindings = ports.iter()
+            .map(|(container, host)| (
+                format!("{}/tcp", container),
+                vec![PortBinding { host_ip: String::new(), host_port: host.to_string() }],
+            ))
+            .collect();
+        Self {
+            image: image.to_string(),
+            exposed_ports,
+            // 不自动删除，这样容器退出后还能查看状态并重新启动
+            host_config: HostConfig { port_bindings, auto_remove: false },
+        }
+    }
+}
+
+// X-Registry-Auth 的内容，与 docker pull 使用的凭据相同
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegistryAuth {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identitytoken: Option<String>,
+    pub serveraddress: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DockerConfig {
+    #[serde(default)]
+    auths: HashMap<String, AuthEntry>,
+    creds_store: Option<String>,
+    #[serde(default)]
+    cred_helpers: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AuthEntry {
+    auth: Option<String>,  // base64 的 "username:password"
+    username: Option<String>,
+    password: Option<String>,
+    identitytoken: Option<String>,
+}
+
+// docker-credential-* get 的输出
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HelperCredentials {
+    username: String,
+    secret: String,
+}
+
+impl RegistryAuth {
+    // 读取 $DOCKER_CONFIG/config.json 或 ~/.docker/config.json；没有配置凭据时匿名拉取
+    pub async fn for_image(image: &str) -> Result<Option<Self>, EngineError> {
+        let dir = env::var_os("DOCKER_CONFIG")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".docker")));
+        match dir {
+            Some(dir) => Self::from_config(&dir.join("config.json"), image).await,
+            None => Ok(None),
+        }
+    }
+
+    // 先看 credHelpers/credsStore，再看 auths 中直接保存的凭据
+    pub async fn from_config(path: &Path, image: &str) -> Result<Option<Self>, EngineError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(EngineError::Credentials(format!("{}: {}", path.display(), e))),
+        };
+        let config: DockerConfig = serde_json::from_str(&content)
+            .map_err(|e| EngineError::Credentials(format!("{}: {}", path.display(), e)))?;
+        let registry = registry_of(image);
+
+        let helper = config.cred_helpers.get(registry).or(config.creds_store.as_ref());
+        if let Some(helper) = helper {
+            let server = if registry == DOCKER_HUB { DOCKER_HUB.to_string() } else { registry.to_string() };
+            if let Some(auth) = Self::from_helper(helper, &server).await? {
+                return Ok(Some(auth));
+            }
+        }
+
+        let Some((server, entry)) = config.auths.iter().find(|(server, _)| registry_key(server) == registry_key(registry)) else {
+            return Ok(None);
+        };
+        let (mut username, mut password) = (entry.username.clone(), entry.password.clone());
+        if let Some(auth) = &entry.auth {
+            let decoded = STANDARD.decode(auth.trim())
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or_else(|| EngineError::Credentials(format!("invalid auth for {} in {}", server, path.display())))?;
+            if let Some((user, secret)) = decoded.split_once(':') {
+                username = Some(user.to_string());
+                password = Some(secret.to_string());
+            }
+        }
+        if username.is_none() && entry.identitytoken.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self { username, password, identitytoken: entry.identitytoken.clone(), serveraddress: server.clone() }))
+    }
+
+    // docker-credential-<helper> get；helper 中没有这个 registry 时返回 None
+    async fn from_helper(helper: &str, server: &str) -> Result<Option<Self>, EngineError> {
+        let program = format!("docker-credential-{}", helper);
+        let mut child = Command::new(&program)
+            .arg("get")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| EngineError::Credentials(format!("{}: {}", program, e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(server.as_bytes()).await
+                .map_err(|e| EngineError::Credentials(format!("{}: {}", program, e)))?;
+        }
+        let output = child.wait_with_output().await
+            .map_err(|e| EngineError::Credentials(format!("{}: {}", program, e)))?;
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if message.contains("credentials not found") {
+                return Ok(None);
+            }
+            return Err(EngineError::Credentials(format!("{} failed: {}", program, message)));
+        }
+        let credentials: HelperCredentials = serde_json::from_slice(&output.stdout)
+            .map_err(|e| EngineError::Credentials(format!("{}: {}", program, e)))?;
+        // 用户名为 <token> 时 secret 是 identity token
+        if credentials.username == "<token>" {
+            return Ok(Some(Self { username: None, password: None, identitytoken: Some(credentials.secret), serveraddress: server.to_string() }));
+        }
+        Ok(Some(Self {
+            username: Some(credentials.username),
+            password: Some(credentials.secret),
+            identitytoken: None,
+            serveraddress: server.to_string(),
+        }))
+    }
+
+    // base64url 编码的 JSON
+    pub fn header(&self) -> String {
+        URL_SAFE.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+}
+
+// "registry.example.com:5000/repo:tag" → "registry.example.com:5000"，没有 registry 部分时为 Docker Hub
+fn registry_of(image: &str) -> &str {
+    match image.split_once('/') {
+        Some((first, _)) if first.contains('.') || first.contains(':') || first == "localhost" => first,
+        _ => DOCKER_HUB,
+    }
+}
+
+// config.json 中的键可能带协议和路径，例如 https://index.docker.io/v1/
+fn registry_key(server: &str) -> &str {
+    let server = server.strip_prefix("https://").or_else(|| server.strip_prefix("http://")).unwrap_or(server);
+    let host = server.split('/').next().unwrap_or(server);
+    match host {
+        "docker.io" | "registry-1.docker.io" => "index.docker.io",
+        _ => host,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CreatedContainer {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineClient {
+    host: DockerHost,
+}
+
+impl EngineClient {
+    pub fn new(host: DockerHost) -> Self {
+        Self { host }
+    }
+
+    pub async fn version(&self) -> Result<Version, EngineError> {
+        self.get_json("/version").await
+    }
+
+    // 拉取镜像，每条进度事件都交给 on_event；私有 registry 需要 auth
+    pub async fn pull_image(&self, image: &str, auth: Option<&RegistryAuth>, mut on_event: impl FnMut(PullEvent)) -> Result<(), EngineError> {
+        let (from_image, tag) = split_image(image);
+        let path = format!("/images/create?fromImage={}&tag={}", encode(from_image), encode(tag));
+        let headers: Vec<(&str, String)> = auth.map(|auth| ("X-Registry-Auth", auth.header())).into_iter().collect();
+        let mut response = self.send_with_headers(Method::POST, &path, None, &headers).await?;
+
+        let mut buffer = BytesMut::new();
+        while let Some(frame) = response.frame().await {
+            if let Ok(data) = frame?.into_data() {
+                buffer.extend_from_slice(&data);
+            }
+            while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+                let line = buffer.split_to(newline + 1);
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let event: PullEvent = serde_json::from_slice(&line)?;
+                // 拉取失败时 API 仍返回 200，错误在事件流中
+                if let Some(error) = event.error {
+                    return Err(EngineError::Api { status: 200, message: error });
+                }
+                on_event(event);
+            }
+        }
+        Ok(())
+    }
+
+    // 按名字精确查找容器（包括已停止的）
+    pub async fn find_container(&self, name: &str) -> Result<Option<ContainerSummary>, EngineError> {
+        let filters = json!({ "name": [format!("^/{}$", escape_regex(name))] }).to_string();
+        let path = format!("/containers/json?all=true&filters={}", encode(&filters));
+        let containers: Vec<ContainerSummary> = self.get_json(&path).await?;
+        Ok(containers.into_iter().next())
+    }
+
+    pub async fn create_container(&self, name: &str, spec: &ContainerSpec) -> Result<String, EngineError> {
+        let path = format!("/containers/create?name={}", encode(name));
+        let body = serde_json::to_vec(spec)?;
+        let response = self.send(Method::POST, &path, Some(Bytes::from(body))).await?;
+        let created: CreatedContainer = read_json(response).await?;
+        Ok(created.id)
+    }
+
+    pub async fn start_container(&self, id: &str) -> Result<(), EngineError> {
+        self.send(Method::POST, &format!("/containers/{}/start", encode(id)), None).await?;
+        Ok(())
+    }
+
+    // 容器不存在时返回 None
+    pub async fn inspect_container(&self, name: &str) -> Result<Option<ContainerInspect>, EngineError> {
+        match self.get_json(&format!("/containers/{}/json", encode(name))).await {
+            Ok(container) => Ok(Some(container)),
+            Err(EngineError::Api { status: 404, .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn stop_container(&self, id: &str) -> Result<(), EngineError> {
+        match self.send(Method::POST, &format!("/containers/{}/stop", encode(id)), None).await {
+            // 304: 容器已经停止
+            Ok(_) | Err(EngineError::Api { status: 304, .. }) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn restart_container(&self, id: &str) -> Result<(), EngineError> {
+        self.send(Method::POST, &format!("/containers/{}/restart", encode(id)), None).await?;
+        Ok(())
+    }
+
+    pub async fn remove_container(&self, id: &str) -> Result<(), EngineError> {
+        self.send(Method::DELETE, &format!("/containers/{}?force=true", encode(id)), None).await?;
+        Ok(())
+    }
+
+    // 跟随容器输出，类似 `docker logs -f --tail N`
+    pub async fn follow_logs(&self, id: &str, tail: usize) -> Result<LogStream, EngineError> {
+        let path = format!("/containers/{}/logs?follow=true&stdout=true&stderr=true&tail={}", encode(id), tail);
+        let response = self.send(Method::GET, &path, None).await?;
+        Ok(LogStream { response, buffer: BytesMut::new(), partial: String::new() })
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, EngineError> {
+        let response = self.send(Method::GET, path, None).await?;
+        read_json(response).await
+    }
+
+    async fn send(&self, method: Method, path: &str, body: Option<Bytes>) -> Result<Response<Incoming>, EngineError> {
+        self.send_with_headers(method, path, body, &[]).await
+    }
+
+    // 每个请求建立一个新连接，非 2xx 响应转换为 EngineError::Api
+    async fn send_with_headers(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Bytes>,
+        headers: &[(&str, String)],
+    ) -> Result<Response<Incoming>, EngineError> {
+        let mut sender = match &self.host {
+            DockerHost::Unix(path) => {
+                let stream = UnixStream::connect(path)
+                    .await
+                    .map_err(|e| EngineError::Connect(self.host.to_string(), e))?;
+                handshake(stream).await?
+            }
+            DockerHost::Tcp(address) => {
+                let stream = TcpStream::connect(address)
+                    .await
+                    .map_err(|e| EngineError::Connect(self.host.to_string(), e))?;
+                handshake(stream).await?
+            }
+        };
+
+        let mut request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::HOST, "docker")
+            .header(header::CONTENT_TYPE, "application/json");
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        let request = request
+            .body(Full::new(body.unwrap_or_default()))
+            .expect("valid Docker API request");
+        let response = sender.send_request(request).await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.into_body().collect().await?.to_bytes();
+        let message = serde_json::from_slice::<ApiErrorBody>(&body)
+            .map(|error| error.message)
+            .unwrap_or_else(|_| String::from_utf8_lossy(&body).trim().to_string());
+        Err(EngineError::Api { status: status.as_u16(), message })
+    }
+}
+
+// 非 TTY 容器的日志是多路复用流：每帧 8 字节头（流类型 + 长度）加数据
+pub struct LogStream {
+    response: Response<Incoming>,
+    buffer: BytesMut,
+    partial: String,
+}
+
+const LOG_FRAME_HEADER: usize = 8;
+
+impl LogStream {
+    // 返回下一批完整的日志行，流结束时返回 None
+    pub async fn next_lines(&mut self) -> Result<Option<Vec<String>>, EngineError> {
+        loop {
+            let Some(frame) = self.response.frame().await else {
+                if self.partial.is_empty() {
+                    return Ok(None);
+                }
+                return Ok(Some(vec![std::mem::take(&mut self.partial)]));
+            };
+            if let Ok(data) = frame?.into_data() {
+                self.buffer.extend_from_slice(&data);
+            }
+
+            while self.buffer.len() >= LOG_FRAME_HEADER {
+                let size = u32::from_be_bytes([self.buffer[4], self.buffer[5], self.buffer[6], self.buffer[7]]) as usize;
+                if self.buffer.len() < LOG_FRAME_HEADER + size {
+                    break;
+                }
+                let frame = self.buffer.split_to(LOG_FRAME_HEADER + size);
+                self.partial.push_str(&String::from_utf8_lossy(&frame[LOG_FRAME_HEADER..]));
+            }
+
+            let Some(end) = self.partial.rfind('\n') else {
+                continue;
+            };
+            let rest = self.partial.split_off(end + 1);
+            let complete = std::mem::replace(&mut self.partial, rest);
+            return Ok(Some(complete.lines().map(str::to_string).collect()));
+        }
+    }
+}
+
+async fn handshake<T>(stream: T) -> Result<http1::SendRequest<Full<Bytes>>, EngineError>
+where
+    T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    let (sender, connection) = http1::handshake(TokioIo::new(stream)).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            log::debug!("Docker API connection closed: {}", e);
+        }
+    });
+    Ok(sender)
+}
+
+async fn read_json<T: DeserializeOwned>(response: Response<Incoming>) -> Result<T, EngineError> {
+    let body = response.into_body().collect().await?.to_bytes();
+    Ok(serde_json::from_slice(&body)?)
+}
+
+// "registry:5000/repo:tag" → ("registry:5000/repo", "tag")
+fn split_image(image: &str) -> (&str, &str) {
+    match image.rsplit_once(':') {
+        Some((name, tag)) if !tag.contains('/') => (name, tag),
+        _ => (image, "latest"),
+    }
+}
+
+fn encode(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// Docker 按正则匹配容器名，名字中的 . 等字符需要转义
+fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if r"\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,130 @@
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use crate::app::Chain;
+use crate::catalog::Catalog;
+
+const MAX_RESULTS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinderTarget {
+    Chain { chain: usize },
+    Table { chain: usize, table: usize },
+    Column { chain: usize, table: usize, column: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct FinderItem {
+    pub target: FinderTarget,
+    pub label: String,   // chain / chain.table / chain.table.column
+    pub detail: String,  // 字段类型和描述
+}
+
+impl FinderItem {
+    pub fn kind(&self) -> &'static str {
+        match self.target {
+            FinderTarget::Chain { .. } => "chain",
+            FinderTarget::Table { .. } => "table",
+            FinderTarget::Column { .. } => "column",
+        }
+    }
+}
+
+// `/` 命令面板：在所有链、表和字段中模糊搜索
+#[derive(Debug, Default, Clone)]
+pub struct Finder {
+    pub query: String,
+    pub selected: usize,
+    items: Vec<FinderItem>,
+    matches: Vec<usize>,
+}
+
+impl Finder {
+    pub fn new(chains: &[Chain], catalog: &Catalog) -> Self {
+        let mut items = Vec::new();
+        for (chain_index, chain) in chains.iter().enumerate() {
+            items.push(FinderItem {
+                target: FinderTarget::Chain { chain: chain_index },
+                label: chain.name.clone(),
+                detail: chain.status.clone(),
+            });
+            for (table_index, table) in catalog.tables(chain_index).iter().enumerate() {
+                items.push(FinderItem {
+                    target: FinderTarget::Table { chain: chain_index, table: table_index },
+                    label: table.id.to_string(),
+                    detail: format!("{} columns", table.columns.len()),
+                });
+                for (column_index, column) in table.columns.iter().enumerate() {
+                    items.push(FinderItem {
+                        target: FinderTarget::Column { chain: chain_index, table: table_index, column: column_index },
+                        label: format!("{}.{}", table.id, column.name),
+                        detail: format!("{} {}", column.dataType, column.description),
+                    });
+                }
+            }
+        }
+
+        let mut finder = Self {
+            items,
+            ..Self::default()
+        };
+        finder.search();
+        finder
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.search();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.search();
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn results(&self) -> impl Iterator<Item = &FinderItem> {
+        self.matches.iter().map(|&i| &self.items[i])
+    }
+
+    pub fn selected_item(&self) -> Option<&FinderItem> {
+        self.matches.get(self.selected).map(|&i| &self.items[i])
+    }
+
+    // 名称匹配优先，描述匹配的得分减半；同分时名字短的靠前
+    fn search(&mut self) {
+        self.selected = 0;
+        let query = self.query.trim();
+        if query.is_empty() {
+            self.matches = self.items.iter()
+                .enumerate()
+                .filter(|(_, item)| !matches!(item.target, FinderTarget::Column { .. }))
+                .map(|(i, _)| i)
+                .take(MAX_RESULTS)
+                .collect();
+            return;
+        }
+
+        let matcher = SkimMatcherV2::default();
+        let mut scored: Vec<(i64, usize)> = self.items.iter()
+            .enumerate()
+            .filter_map(|(i, item)| {
+                let label = matcher.fuzzy_match(&item.label, query);
+                let detail = matcher.fuzzy_match(&item.detail, query).map(|score| score / 2);
+                label.max(detail).map(|score| (score, i))
+            })
+            .collect();
+        scored.sort_by(|(a_score, a), (b_score, b)| {
+            b_score.cmp(a_score)
+                .then(self.items[*a].label.len().cmp(&self.items[*b].label.len()))
+        });
+        self.matches = scored.into_iter().take(MAX_RESULTS).map(|(_, i)| i).collect();
+    }
+}
@@ -0,0 +1,226 @@
+use std::cell::Cell;
+use ratatui::{
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    symbols::border,
+    text::{Line, Text},
+    widgets::{Block, Clear, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+use serde_json::Value;
+use crate::app::Column;
+
+const MAX_COLUMN_WIDTH: usize = 40;
+const COLUMN_SPACING: usize = 1;
+
+// 表格光标和滚动位置，偏移量在渲染时根据可见区域调整
+#[derive(Debug, Default, Clone)]
+pub struct GridState {
+    pub row: usize,
+    pub column: usize,
+    pub show_cell: bool,
+    row_offset: Cell<usize>,
+    column_offset: Cell<usize>,
+}
+
+impl GridState {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn up(&mut self, amount: usize) {
+        self.row = self.row.saturating_sub(amount);
+    }
+
+    pub fn down(&mut self, amount: usize, rows: usize) {
+        self.row = (self.row + amount).min(rows.saturating_sub(1));
+    }
+
+    pub fn left(&mut self) {
+        self.column = self.column.saturating_sub(1);
+    }
+
+    pub fn right(&mut self, columns: usize) {
+        self.column = (self.column + 1).min(columns.saturating_sub(1));
+    }
+
+    pub fn first_row(&mut self) {
+        self.row = 0;
+    }
+
+    pub fn last_row(&mut self, rows: usize) {
+        self.row = rows.saturating_sub(1);
+    }
+}
+
+// 根据列类型推算显示宽度
+pub fn column_width(column: &Column) -> usize {
+    let type_ = column.type_.to_lowercase();
+    let type_width = if type_.starts_with("varchar(") {
+        type_.trim_start_matches("varchar(")
+            .trim_end_matches(')')
+            .parse::<usize>()
+            .map(|n| n.min(24))
+            .unwrap_or(24)
+    } else if type_.contains("timestamp") {
+        23
+    } else if type_ == "date" {
+        10
+    } else if type_ == "boolean" {
+        7
+    } else if type_.contains("int") {
+        12
+    } else if type_ == "double" || type_ == "real" || type_.starts_with("decimal") {
+        16
+    } else {
+        24
+    };
+    type_width.max(column.name.len()).min(MAX_COLUMN_WIDTH)
+}
+
+pub fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+// 超长值加省略号，十六进制哈希保留首尾
+pub fn truncate(value: &str, width: usize) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= width {
+        return value.to_string();
+    }
+    if width <= 1 {
+        return "…".to_string();
+    }
+    if value.starts_with("0x") && width >= 7 {
+        let head = (width - 1) / 2;
+        let tail = width - 1 - head;
+        let start: String = chars[..head].iter().collect();
+        let end: String = chars[chars.len() - tail..].iter().collect();
+        format!("{}…{}", start, end)
+    } else {
+        let start: String = chars[..width - 1].iter().collect();
+        format!("{}…", start)
+    }
+}
+
+pub fn render_grid(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    columns: &[Column],
+    rows: &[Vec<Value>],
+    state: &GridState,
+    focused: bool,
+) {
+    let block = Block::bordered()
+        .title(format!(" {} ", title))
+        .title_alignment(Alignment::Center)
+        .border_set(if focused { border::DOUBLE } else { border::PLAIN })
+        .border_style(if focused { Style::default().fg(Color::Yellow) } else { Style::default() });
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if columns.is_empty() || inner.width == 0 || inner.height < 2 {
+        return;
+    }
+
+    let widths: Vec<usize> = columns.iter().map(column_width).collect();
+    let row = state.row.min(rows.len().saturating_sub(1));
+    let column = state.column.min(columns.len() - 1);
+
+    // 保证选中列可见
+    let mut column_offset = state.column_offset.get().min(column);
+    while column_offset < column
+        && widths[column_offset..=column].iter().map(|w| w + COLUMN_SPACING).sum::<usize>() > inner.width as usize
+    {
+        column_offset += 1;
+    }
+    state.column_offset.set(column_offset);
+
+    let mut visible_columns = Vec::new();
+    let mut used = 0;
+    for (i, width) in widths.iter().enumerate().skip(column_offset) {
+        let width = (*width).min(inner.width as usize);
+        if used + width > inner.width as usize && !visible_columns.is_empty() {
+            break;
+        }
+        used += width + COLUMN_SPACING;
+        visible_columns.push((i, width));
+    }
+
+    // 保证选中行可见，表头占用一行
+    let body_height = (inner.height as usize).saturating_sub(1).max(1);
+    let mut row_offset = state.row_offset.get().min(row);
+    if row >= row_offset + body_height {
+        row_offset = row + 1 - body_height;
+    }
+    state.row_offset.set(row_offset);
+
+    let header = Row::new(visible_columns.iter().map(|(i, width)| {
+        let style = if focused && *i == column {
+            Style::default().fg(Color::Yellow).bold()
+        } else {
+            Style::default().fg(Color::White).bold()
+        };
+        Line::from(truncate(&columns[*i].name, *width)).style(style)
+    }));
+
+    let body = rows.iter()
+        .enumerate()
+        .skip(row_offset)
+        .take(body_height)
+        .map(|(r, values)| {
+            Row::new(visible_columns.iter().map(|(i, width)| {
+                let text = values.get(*i).map(display_value).unwrap_or_default();
+                let mut style = if values.get(*i).is_some_and(Value::is_null) {
+                    Style::default().fg(Color::DarkGray)
+                } else {
+                    Style::default()
+                };
+                if r == row && *i == column && focused {
+                    style = style.bg(Color::Yellow).fg(Color::Black);
+                } else if r == row {
+                    style = style.add_modifier(Modifier::BOLD).fg(Color::Cyan);
+                }
+                Line::from(truncate(&text, *width)).style(style)
+            }))
+        });
+
+    let constraints: Vec<Constraint> = visible_columns.iter()
+        .map(|(_, width)| Constraint::Length(*width as u16))
+        .collect();
+    let table = Table::new(body, constraints)
+        .header(header)
+        .column_spacing(COLUMN_SPACING as u16);
+    frame.render_widget(table, inner);
+}
+
+// 弹窗显示选中单元格的完整内容
+pub fn render_cell_popup(frame: &mut Frame, columns: &[Column], rows: &[Vec<Value>], state: &GridState) {
+    let (Some(column), Some(values)) = (columns.get(state.column), rows.get(state.row)) else {
+        return;
+    };
+    let value = values.get(state.column).map(display_value).unwrap_or_default();
+
+    let area = frame.area();
+    let width = (area.width as f32 * 0.6) as u16;
+    let height = (area.height as f32 * 0.4) as u16;
+    let popup = Rect::new((area.width - width) / 2, (area.height - height) / 2, width, height);
+
+    let block = Block::bordered()
+        .title(format!(" {} ({}) — row {} ", column.name, column.type_, state.row + 1))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(" Esc/Enter: Close ").centered())
+        .border_set(border::THICK)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(Text::from(value)).block(block).wrap(Wrap { trim: false }),
+        popup,
+    );
+}
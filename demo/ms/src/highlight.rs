@@ -0,0 +1,94 @@
+use std::{collections::HashSet, ops::Range};
+use crate::catalog::{Catalog, CatalogTable};
+use crate::lexer::{self, Token, TokenKind};
+
+// SQL 编辑器中每个 token 的类别，颜色由 ui 决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    Keyword,
+    String,
+    Number,
+    Comment,
+    Operator,
+    Punctuation,
+    Table,     // 链名或数据字典中的表
+    Column,    // 数据字典中的字段
+    Function,
+    Alias,     // 查询里自己定义的名字
+    Unknown,   // 数据字典中找不到，多半是拼写错误
+}
+
+// chain_index 为当前选中的链，不带链名的表和字段在它的数据字典中查找
+pub fn highlight(sql: &str, catalog: &Catalog, chain_index: usize) -> Vec<(Range<usize>, Highlight)> {
+    let tokens = lexer::tokenize(sql);
+    let is = |i: usize, text: &str| tokens.get(i).is_some_and(|token| token.is(sql, text));
+    let is_identifier = |i: usize| tokens.get(i).is_some_and(Token::is_identifier);
+    // 跳过注释，找前一个/后一个有意义的 token
+    let previous = |i: usize| (0..i).rev().find(|&j| tokens[j].kind != TokenKind::Comment);
+    let next = |i: usize| (i + 1..tokens.len()).find(|&j| tokens[j].kind != TokenKind::Comment);
+
+    // 查询引用的表：选中链的所有表加上 chain.table 写法引用的其他链的表
+    let mut tables: Vec<&CatalogTable> = catalog.tables(chain_index).iter().collect();
+    for i in 0..tokens.len() {
+        if is_identifier(i) && is(i + 1, ".") && is_identifier(i + 2) {
+            if let Some(table) = catalog.find(tokens[i].name(sql), tokens[i + 2].name(sql)) {
+                if !tables.iter().any(|known| known.id == table.id) {
+                    tables.push(table);
+                }
+            }
+        }
+    }
+    let is_table = |name: &str| tables.iter().any(|table| table.name == name);
+    let is_column = |name: &str| tables.iter().any(|table| table.columns.iter().any(|column| column.name == name));
+
+    // AS 后面、FROM/JOIN 的表后面以及紧跟在表达式后面的名字都是别名
+    let mut aliases = HashSet::new();
+    for i in 0..tokens.len() {
+        if !is_identifier(i) {
+            continue;
+        }
+        let Some(before) = previous(i) else {
+            continue;
+        };
+        let token = &tokens[before];
+        let follows_expression = is(before, ")")
+            || token.is_keyword(sql, "AS")
+            || token.is_keyword(sql, "END")
+            || matches!(token.kind, TokenKind::Identifier | TokenKind::QuotedIdentifier | TokenKind::Number | TokenKind::String);
+        // 例如 count(*) cnt、FROM ethereum.blocks b 和 x AS y
+        if follows_expression && !is(i + 1, ".") && !is(i + 1, "(") {
+            aliases.insert(tokens[i].name(sql));
+        }
+    }
+
+    tokens.iter().enumerate().map(|(i, token)| {
+        let highlight = match token.kind {
+            TokenKind::Keyword => Highlight::Keyword,
+            TokenKind::String => Highlight::String,
+            TokenKind::Number => Highlight::Number,
+            TokenKind::Comment => Highlight::Comment,
+            TokenKind::Operator => Highlight::Operator,
+            TokenKind::Punctuation => Highlight::Punctuation,
+            TokenKind::Identifier | TokenKind::QuotedIdentifier => {
+                let name = token.name(sql);
+                let qualifier = (i >= 2 && is(i - 1, ".") && is_identifier(i - 2)).then(|| tokens[i - 2].name(sql));
+                match qualifier {
+                    // chain.table
+                    Some(chain) if catalog.chain_index(chain).is_some() => {
+                        if catalog.find(chain, name).is_some() { Highlight::Table } else { Highlight::Unknown }
+                    }
+                    // table.column 或 alias.column
+                    Some(_) if is_column(name) => Highlight::Column,
+                    Some(_) => Highlight::Unknown,
+                    None if next(i).is_some_and(|j| is(j, "(")) => Highlight::Function,
+                    None if is(i + 1, ".") && catalog.chain_index(name).is_some() => Highlight::Table,
+                    None if is_table(name) => Highlight::Table,
+                    None if is_column(name) => Highlight::Column,
+                    None if aliases.contains(name) => Highlight::Alias,
+                    None => Highlight::Unknown,
+                }
+            }
+        };
+        (token.span.clone(), highlight)
+    }).collect()
+}
@@ -0,0 +1,339 @@
+use std::{cell::Cell, time::Duration};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::metrics::{AggregatedMetric, CheckpointStats, JobDashboard, JobDetail, JobMetrics, VertexMetrics, VERTEX_METRICS};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// /jobs/overview 中的一个作业
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobSummary {
+    #[serde(rename = "jid")]
+    pub id: String,
+    pub name: String,
+    pub state: String,
+    #[serde(rename = "start-time", default)]
+    pub start_time: i64,
+    #[serde(default)]
+    pub duration: i64,  // 运行中的作业为到现在为止的时长
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JobGroup {
+    Running,
+    Failed,
+    Finished,
+}
+
+impl JobSummary {
+    pub fn group(&self) -> JobGroup {
+        match self.state.as_str() {
+            "FINISHED" | "CANCELED" => JobGroup::Finished,
+            "FAILED" | "FAILING" => JobGroup::Failed,
+            _ => JobGroup::Running,
+        }
+    }
+
+    // 已经结束的作业不能再取消
+    pub fn can_cancel(&self) -> bool {
+        self.group() == JobGroup::Running && self.state != "CANCELLING"
+    }
+
+    pub fn started(&self) -> String {
+        format_time(self.start_time)
+    }
+
+    pub fn elapsed(&self) -> String {
+        format_duration(self.duration)
+    }
+}
+
+// /jobs/:id/exceptions 的 exceptionHistory 中的一项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionEntry {
+    #[serde(default)]
+    pub exception_name: String,
+    #[serde(default)]
+    pub stacktrace: String,
+    #[serde(default)]
+    pub timestamp: i64,
+    pub task_name: Option<String>,
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ExceptionHistory {
+    #[serde(default)]
+    entries: Vec<ExceptionEntry>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct JobExceptions {
+    #[serde(rename = "root-exception")]
+    pub root_exception: Option<String>,
+    pub timestamp: Option<i64>,
+    #[serde(rename = "exceptionHistory", default)]
+    history: ExceptionHistory,
+}
+
+impl JobExceptions {
+    // 最新的在前
+    pub fn entries(&self) -> &[ExceptionEntry] {
+        &self.history.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root_exception.is_none() && self.history.entries.is_empty()
+    }
+
+    // 异常面板和 `ms jobs exceptions` 共用的文本
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(root) = &self.root_exception {
+            lines.push(format!("Root exception ({})", self.timestamp.map(format_time).unwrap_or_default()));
+            lines.extend(root.lines().map(str::to_string));
+        }
+        for entry in self.entries() {
+            // root exception 通常也是历史中的第一项
+            if self.root_exception.as_deref().is_some_and(|root| root == entry.stacktrace) {
+                continue;
+            }
+            lines.push(String::new());
+            let mut header = format!("{} ({})", entry.exception_name, format_time(entry.timestamp));
+            if let Some(task) = &entry.task_name {
+                header.push_str(&format!(" in {}", task));
+            }
+            if let Some(location) = &entry.location {
+                header.push_str(&format!(" at {}", location));
+            }
+            lines.push(header);
+            lines.extend(entry.stacktrace.lines().map(str::to_string));
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct JobsOverview {
+    jobs: Vec<JobSummary>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Jar {
+    id: String,
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Jars {
+    #[serde(default)]
+    files: Vec<Jar>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunRequest<'a> {
+    program_args_list: [&'a str; 2],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parallelism: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RunResponse {
+    jobid: String,
+}
+
+// 调试节点上 job manager 的 REST API（Flink JobManager，容器内 8081 端口）
+#[derive(Debug, Clone)]
+pub struct JobClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl JobClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // 运行中的在前，同一组内最新提交的在前
+    pub async fn jobs(&self) -> Result<Vec<JobSummary>, String> {
+        let request = self.http.get(format!("{}/jobs/overview", self.base_url));
+        let mut jobs = serde_json::from_value::<JobsOverview>(self.fetch(request).await?)
+            .map_err(|e| format!("Invalid response: {}", e))?
+            .jobs;
+        jobs.sort_by(|a, b| a.group().cmp(&b.group()).then(b.start_time.cmp(&a.start_time)));
+        Ok(jobs)
+    }
+
+    // 镜像里预先上传了 manuscript runner 的 jar，把 manuscript 内容作为参数交给它运行
+    pub async fn submit(&self, content: &str, parallelism: Option<u32>) -> Result<String, String> {
+        let request = self.http.get(format!("{}/jars", self.base_url));
+        let jars = serde_json::from_value::<Jars>(self.fetch(request).await?)
+            .map_err(|e| format!("Invalid response: {}", e))?
+            .files;
+        let jar = jars.iter()
+            .find(|jar| jar.name.contains("manuscript"))
+            .ok_or_else(|| "No manuscript runner jar is uploaded to the job manager".to_string())?;
+
+        let request = self.http
+            .post(format!("{}/jars/{}/run", self.base_url, jar.id))
+            .json(&RunRequest { program_args_list: ["--manuscript", content], parallelism });
+        serde_json::from_value::<RunResponse>(self.fetch(request).await?)
+            .map(|response| response.jobid)
+            .map_err(|e| format!("Invalid response: {}", e))
+    }
+
+    pub async fn cancel(&self, id: &str) -> Result<(), String> {
+        let request = self.http.patch(format!("{}/jobs/{}?mode=cancel", self.base_url, id));
+        self.fetch(request).await.map(|_| ())
+    }
+
+    pub async fn exceptions(&self, id: &str) -> Result<JobExceptions, String> {
+        let request = self.http.get(format!("{}/jobs/{}/exceptions", self.base_url, id));
+        serde_json::from_value(self.fetch(request).await?)
+            .map_err(|e| format!("Invalid response: {}", e))
+    }
+
+    // 每个算子的吞吐和反压，以及 checkpoint 统计
+    pub async fn metrics(&self, id: &str) -> Result<JobMetrics, String> {
+        let request = self.http.get(format!("{}/jobs/{}", self.base_url, id));
+        let detail = serde_json::from_value::<JobDetail>(self.fetch(request).await?)
+            .map_err(|e| format!("Invalid response: {}", e))?;
+
+        let mut vertices = Vec::new();
+        for vertex in &detail.vertices {
+            let request = self.http.get(format!(
+                "{}/jobs/{}/vertices/{}/subtasks/metrics?get={}",
+                self.base_url, id, vertex.id, VERTEX_METRICS.join(",")
+            ));
+            let metrics = serde_json::from_value::<Vec<AggregatedMetric>>(self.fetch(request).await?)
+                .map_err(|e| format!("Invalid response: {}", e))?;
+            vertices.push(VertexMetrics::new(vertex, &metrics));
+        }
+
+        let request = self.http.get(format!("{}/jobs/{}/checkpoints", self.base_url, id));
+        let checkpoints = serde_json::from_value::<CheckpointStats>(self.fetch(request).await?)
+            .map_err(|e| format!("Invalid response: {}", e))?;
+        Ok(JobMetrics { vertices, checkpoints })
+    }
+
+    // 错误响应的格式为 {"errors": ["..."]}
+    async fn fetch(&self, request: reqwest::RequestBuilder) -> Result<Value, String> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach job manager at {}: {}", self.base_url, e))?;
+
+        let status = response.status();
+        let body = response.text().await.map_err(|e| format!("Invalid response: {}", e))?;
+        if !status.is_success() {
+            let errors = serde_json::from_str::<Value>(&body).ok()
+                .and_then(|document| document.get("errors")?.as_array().cloned())
+                .map(|errors| errors.iter().filter_map(|e| e.as_str()).map(first_line).collect::<Vec<_>>().join("; "))
+                .filter(|errors| !errors.is_empty())
+                .unwrap_or_else(|| body.trim().to_string());
+            return Err(format!("Job manager returned {}: {}", status, errors));
+        }
+        if body.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&body).map_err(|e| format!("Invalid response: {}", e))
+    }
+}
+
+// 服务端异常的第一行，完整堆栈太长
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+pub fn format_time(millis: i64) -> String {
+    if millis <= 0 {
+        return "-".to_string();
+    }
+    Local.timestamp_millis_opt(millis)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+pub fn format_duration(millis: i64) -> String {
+    let seconds = millis.max(0) / 1000;
+    format!("{}h {:02}m {:02}s", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+// 作业面板：列表、取消确认和异常详情
+#[derive(Debug, Clone, Default)]
+pub struct JobsView {
+    pub jobs: Vec<JobSummary>,
+    pub error: Option<String>,
+    pub loaded: bool,
+    pub selected: usize,
+    pub confirm_cancel: bool,
+    pub message: Option<String>,
+    pub exceptions: Option<(String, Result<JobExceptions, String>)>,  // 正在查看的作业 id → 异常
+    pub dashboard: Option<JobDashboard>,
+    pub scroll: usize,
+    pub height: Cell<usize>,  // 上一次渲染时异常详情的可见行数
+}
+
+impl JobsView {
+    pub fn set_jobs(&mut self, result: Result<Vec<JobSummary>, String>) {
+        self.loaded = true;
+        match result {
+            Ok(jobs) => {
+                // 刷新后保持选中同一个作业
+                let selected = self.selected_job().map(|job| job.id.clone());
+                self.jobs = jobs;
+                self.selected = selected
+                    .and_then(|id| self.jobs.iter().position(|job| job.id == id))
+                    .unwrap_or(self.selected)
+                    .min(self.jobs.len().saturating_sub(1));
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    pub fn selected_job(&self) -> Option<&JobSummary> {
+        self.jobs.get(self.selected)
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.jobs.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn count(&self, group: JobGroup) -> usize {
+        self.jobs.iter().filter(|job| job.group() == group).count()
+    }
+
+    pub fn scroll_up(&mut self, amount: usize) {
+        self.scroll = self.scroll.saturating_sub(amount);
+    }
+
+    pub fn scroll_down(&mut self, amount: usize) {
+        let total = match &self.exceptions {
+            Some((_, Ok(exceptions))) => exceptions.lines().len(),
+            _ => 0,
+        };
+        self.scroll = (self.scroll + amount).min(total.saturating_sub(self.height.get().max(1)));
+    }
+}
@@ -0,0 +1,215 @@
+use std::ops::Range;
+
+// Flink / Trino SQL 中常见的关键字，匹配时忽略大小写
+const KEYWORDS: &[&str] = &[
+    "ALL", "AND", "AS", "ASC", "BETWEEN", "BOTH", "BY", "CASE", "CAST", "CROSS", "CURRENT_DATE",
+    "CURRENT_TIME", "CURRENT_TIMESTAMP", "DATE", "DAY", "DESC", "DISTINCT", "ELSE", "END",
+    "EXISTS", "FALSE", "FETCH", "FIRST", "FOR", "FROM", "FULL", "GROUP", "HAVING", "HOUR", "IN",
+    "INNER", "INSERT", "INTERVAL", "INTO", "IS", "JOIN", "LATERAL", "LEADING", "LEFT", "LIKE", "LIMIT",
+    "MINUTE", "MONTH", "NEXT", "NOT", "NULL", "OFFSET", "ON", "ONLY", "OR", "ORDER", "OUTER",
+    "OVER", "PARTITION", "RANGE", "RIGHT", "ROWS", "SECOND", "SELECT", "TABLE", "THEN", "TIME",
+    "TIMESTAMP", "TRAILING", "TRUE", "UNION", "UNNEST", "USING", "VALUES", "WHEN", "WHERE", "WINDOW", "WITH",
+    "YEAR",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    Identifier,
+    QuotedIdentifier,  // "name" 或 `name`
+    String,
+    Number,
+    Comment,
+    Operator,
+    Punctuation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,  // 在 SQL 文本中的字节范围
+}
+
+impl Token {
+    pub fn text<'a>(&self, sql: &'a str) -> &'a str {
+        &sql[self.span.clone()]
+    }
+
+    // 标识符的名字，带引号的去掉引号
+    pub fn name<'a>(&self, sql: &'a str) -> &'a str {
+        let text = self.text(sql);
+        match self.kind {
+            TokenKind::QuotedIdentifier if text.len() >= 2 => &text[1..text.len() - 1],
+            _ => text,
+        }
+    }
+
+    pub fn is_identifier(&self) -> bool {
+        matches!(self.kind, TokenKind::Identifier | TokenKind::QuotedIdentifier)
+    }
+
+    pub fn is_keyword(&self, sql: &str, keyword: &str) -> bool {
+        self.kind == TokenKind::Keyword && self.text(sql).eq_ignore_ascii_case(keyword)
+    }
+
+    pub fn is(&self, sql: &str, text: &str) -> bool {
+        self.text(sql) == text
+    }
+}
+
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+// 切分 SQL，空白不产生 token；未闭合的字符串和注释延续到末尾
+pub fn tokenize(sql: &str) -> Vec<Token> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        let kind = match c {
+            _ if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = sql[i..].find('\n').map(|end| i + end).unwrap_or(bytes.len());
+                TokenKind::Comment
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = sql[i + 2..].find("*/").map(|end| i + 2 + end + 2).unwrap_or(bytes.len());
+                TokenKind::Comment
+            }
+            b'\'' => {
+                i = closing_quote(bytes, i, b'\'');
+                TokenKind::String
+            }
+            b'"' | b'`' => {
+                i = closing_quote(bytes, i, c);
+                TokenKind::QuotedIdentifier
+            }
+            b'0'..=b'9' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.') {
+                    i += 1;
+                }
+                TokenKind::Number
+            }
+            _ if c.is_ascii_alphabetic() || c == b'_' || c >= 0x80 => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'$' || bytes[i] >= 0x80) {
+                    i += 1;
+                }
+                if is_keyword(&sql[start..i]) { TokenKind::Keyword } else { TokenKind::Identifier }
+            }
+            b'<' | b'>' | b'!' | b'=' | b'|' | b':' => {
+                i += 1;
+                if matches!(bytes.get(i), Some(b'=') | Some(b'>') | Some(b'|') | Some(b':')) {
+                    i += 1;
+                }
+                TokenKind::Operator
+            }
+            b'+' | b'-' | b'*' | b'/' | b'%' => {
+                i += 1;
+                TokenKind::Operator
+            }
+            _ => {
+                i += 1;
+                TokenKind::Punctuation
+            }
+        };
+        tokens.push(Token { kind, span: start..i });
+    }
+    tokens
+}
+
+// 引号内两个连续的引号表示转义
+fn closing_quote(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        if bytes[i] == quote {
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(sql: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(sql).into_iter().map(|token| (token.kind, token.text(sql))).collect()
+    }
+
+    #[test]
+    fn splits_keywords_identifiers_and_punctuation() {
+        assert_eq!(kinds("select b.hash, count(*) FROM ethereum.blocks b"), vec![
+            (TokenKind::Keyword, "select"),
+            (TokenKind::Identifier, "b"),
+            (TokenKind::Punctuation, "."),
+            (TokenKind::Identifier, "hash"),
+            (TokenKind::Punctuation, ","),
+            (TokenKind::Identifier, "count"),
+            (TokenKind::Punctuation, "("),
+            (TokenKind::Operator, "*"),
+            (TokenKind::Punctuation, ")"),
+            (TokenKind::Keyword, "FROM"),
+            (TokenKind::Identifier, "ethereum"),
+            (TokenKind::Punctuation, "."),
+            (TokenKind::Identifier, "blocks"),
+            (TokenKind::Identifier, "b"),
+        ]);
+    }
+
+    #[test]
+    fn literals_comments_and_operators() {
+        assert_eq!(kinds("x >= 1.5 -- note\nAND y <> 'it''s' /* c */ || \"Name\""), vec![
+            (TokenKind::Identifier, "x"),
+            (TokenKind::Operator, ">="),
+            (TokenKind::Number, "1.5"),
+            (TokenKind::Comment, "-- note"),
+            (TokenKind::Keyword, "AND"),
+            (TokenKind::Identifier, "y"),
+            (TokenKind::Operator, "<>"),
+            (TokenKind::String, "'it''s'"),
+            (TokenKind::Comment, "/* c */"),
+            (TokenKind::Operator, "||"),
+            (TokenKind::QuotedIdentifier, "\"Name\""),
+        ]);
+    }
+
+    #[test]
+    fn unterminated_string_and_comment_run_to_the_end() {
+        assert_eq!(kinds("a = 'open"), vec![
+            (TokenKind::Identifier, "a"),
+            (TokenKind::Operator, "="),
+            (TokenKind::String, "'open"),
+        ]);
+        assert_eq!(kinds("a /* open"), vec![(TokenKind::Identifier, "a"), (TokenKind::Comment, "/* open")]);
+    }
+
+    #[test]
+    fn function_call_keywords() {
+        let sql = "EXTRACT(YEAR FROM ts), TRIM(LEADING '0' FROM s), SUBSTRING(s FROM 1 FOR 3)";
+        let keywords: Vec<&str> = tokenize(sql).into_iter()
+            .filter(|token| token.kind == TokenKind::Keyword)
+            .map(|token| token.text(sql))
+            .collect();
+        assert_eq!(keywords, vec!["YEAR", "FROM", "LEADING", "FROM", "FROM", "FOR"]);
+    }
+
+    #[test]
+    fn quoted_identifier_name_drops_the_quotes() {
+        let sql = "`weird name`";
+        let tokens = tokenize(sql);
+        assert_eq!(tokens.len(), 1);
+        assert!(tokens[0].is_identifier());
+        assert_eq!(tokens[0].name(sql), "weird name");
+    }
+}
@@ -0,0 +1,2 @@
+// 与界面无关、可以单独测试的部分
+pub mod engine;
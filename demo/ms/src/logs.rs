@@ -0,0 +1,152 @@
+use std::{cell::Cell, collections::VecDeque, fs, io, path::PathBuf};
+use chrono::Local;
+
+// 最多保留的日志行数，超出后丢弃最旧的
+const MAX_LOG_LINES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Other,
+}
+
+impl LogLevel {
+    // log4j/slf4j 风格的日志，级别出现在行首附近
+    pub fn detect(line: &str) -> Self {
+        let head: String = line.chars().take(80).collect();
+        if head.contains("ERROR") || head.contains("FATAL") || head.starts_with("Exception") {
+            LogLevel::Error
+        } else if head.contains("WARN") {
+            LogLevel::Warn
+        } else if head.contains("INFO") {
+            LogLevel::Info
+        } else if head.contains("DEBUG") || head.contains("TRACE") {
+            LogLevel::Debug
+        } else {
+            LogLevel::Other
+        }
+    }
+}
+
+// 日志面板：跟随输出、暂停、搜索和保存
+#[derive(Debug, Clone, Default)]
+pub struct LogView {
+    pub lines: VecDeque<String>,
+    pub paused: bool,
+    pub offset: usize,          // 距离底部的行数，0 表示跟随最新输出
+    pub unseen: usize,          // 暂停期间新到达的行数
+    pub search: String,
+    pub searching: bool,        // 正在输入搜索词
+    pub following: bool,        // 日志流是否仍在连接
+    pub message: Option<String>,
+    pub height: Cell<usize>,    // 上一次渲染时的可见行数
+}
+
+impl LogView {
+    pub fn push(&mut self, lines: Vec<String>) {
+        let added = lines.len();
+        self.lines.extend(lines);
+        let dropped = self.lines.len().saturating_sub(MAX_LOG_LINES);
+        self.lines.drain(..dropped);
+
+        // 暂停时保持当前可见的内容不动
+        if self.paused {
+            self.unseen += added;
+            self.offset = (self.offset + added).min(self.lines.len());
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.paused = true;
+        }
+    }
+
+    fn resume(&mut self) {
+        self.paused = false;
+        self.offset = 0;
+        self.unseen = 0;
+    }
+
+    // 向上滚动会自动暂停跟随
+    pub fn scroll_up(&mut self, amount: usize) {
+        let max = self.lines.len().saturating_sub(self.height.get());
+        self.offset = (self.offset + amount).min(max);
+        self.paused = self.paused || self.offset > 0;
+    }
+
+    pub fn scroll_down(&mut self, amount: usize) {
+        self.offset = self.offset.saturating_sub(amount);
+        if self.offset == 0 {
+            self.resume();
+        }
+    }
+
+    pub fn top(&mut self) {
+        self.scroll_up(self.lines.len());
+    }
+
+    pub fn bottom(&mut self) {
+        self.resume();
+    }
+
+    // 当前可见的行范围
+    pub fn visible(&self) -> std::ops::Range<usize> {
+        let end = self.lines.len().saturating_sub(self.offset);
+        end.saturating_sub(self.height.get())..end
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        !self.search.is_empty() && line.to_ascii_lowercase().contains(&self.search.to_ascii_lowercase())
+    }
+
+    // 行内所有匹配的字节范围（忽略 ASCII 大小写）
+    pub fn match_ranges(&self, line: &str) -> Vec<std::ops::Range<usize>> {
+        if self.search.is_empty() {
+            return Vec::new();
+        }
+        let needle = self.search.to_ascii_lowercase();
+        line.to_ascii_lowercase()
+            .match_indices(&needle)
+            .map(|(start, found)| start..start + found.len())
+            .collect()
+    }
+
+    // 跳到上一个 (older) 或下一个 (newer) 匹配行，并把它放在可见区域中间
+    pub fn jump_to_match(&mut self, older: bool) {
+        if self.search.is_empty() {
+            return;
+        }
+        let height = self.height.get();
+        let visible = self.visible();
+        let center = visible.start + (visible.end - visible.start) / 2;
+        let found = if older {
+            (0..center).rev().find(|&i| self.is_match(&self.lines[i]))
+        } else {
+            (center + 1..self.lines.len()).find(|&i| self.is_match(&self.lines[i]))
+        };
+        match found {
+            Some(index) => {
+                let end = (index + height / 2 + 1).min(self.lines.len());
+                self.offset = self.lines.len() - end;
+                self.paused = true;
+                self.message = None;
+            }
+            None => self.message = Some(format!("No more matches for \"{}\"", self.search)),
+        }
+    }
+
+    // 保存到当前目录下带时间戳的文件
+    pub fn save(&self, container_name: &str) -> io::Result<PathBuf> {
+        let path = PathBuf::from(format!("{}-{}.log", container_name, Local::now().format("%Y%m%d-%H%M%S")));
+        let mut content = self.lines.iter().cloned().collect::<Vec<_>>().join("\n");
+        content.push('\n');
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+}
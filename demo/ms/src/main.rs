@@ -1,17 +1,285 @@
-use std::io;
-use ratatui::DefaultTerminal;
+use std::{io, path::PathBuf};
+use clap::{Parser, Subcommand};
+use ms::engine;
+use crossterm::{
+    event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    execute,
+    terminal::supports_keyboard_enhancement,
+};
 
 mod app;
 mod ui;
 mod docker;
+mod sql;
+mod grid;
+mod metadata;
+mod config;
+mod catalog;
+mod finder;
+mod pull;
+mod logs;
+mod ports;
+mod manuscript;
+mod editor;
+mod lexer;
+mod validate;
+mod jobs;
+mod metrics;
+mod highlight;
+mod completion;
+
+#[derive(Debug, Parser)]
+#[command(about = "Chainbase Manuscript debug console")]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Don't touch the network; load chain metadata from the local cache
+    #[arg(long)]
+    offline: bool,
+
+    /// Load chain metadata from a local JSON file instead of the API
+    #[arg(long, value_name = "PATH")]
+    fixture: Option<PathBuf>,
+
+    /// Config file to use instead of the one in the user config dir
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Chain metadata endpoint
+    #[arg(long, value_name = "URL")]
+    metadata_url: Option<String>,
+
+    /// manuscript-debug image to pull and run
+    #[arg(long)]
+    image: Option<String>,
+
+    /// Name of the debug container
+    #[arg(long, value_name = "NAME")]
+    container_name: Option<String>,
+
+    /// Host port mapped to the container's SQL endpoint (8083)
+    #[arg(long, value_name = "PORT")]
+    sql_port: Option<u16>,
+
+    /// Host port mapped to the container's job manager (8081)
+    #[arg(long, value_name = "PORT")]
+    job_port: Option<u16>,
+
+    /// Pick free host ports when the configured ones are already in use
+    #[arg(long)]
+    auto_ports: bool,
+
+    /// Table list order: "api" keeps the API order, "name" sorts alphabetically
+    #[arg(long, value_name = "ORDER")]
+    table_sort: Option<catalog::TableSort>,
+
+    /// Debug environment to use at startup, from [profiles.<name>] in the config
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Directory scanned for manuscript files (default ~/manuscripts)
+    #[arg(long, value_name = "DIR")]
+    workspace: Option<PathBuf>,
+
+    /// Show the built-in mock rows instead of fetching live table samples
+    #[arg(long)]
+    demo: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Check manuscripts against the chain data dictionary without starting the TUI.
+    /// Exits with status 1 when any file has errors
+    Validate {
+        /// Manuscript files to check; defaults to every manuscript in the workspace
+        files: Vec<PathBuf>,
+    },
+    /// Submit and manage jobs on the debug node's job manager
+    ///
+    /// Connects to the configured job port of the profile. Ports picked by auto_ports
+    /// when the TUI started the container are not remembered; pass --job-port to reach them
+    Jobs {
+        #[command(subcommand)]
+        command: JobsCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum JobsCommand {
+    /// List running, finished and failed jobs
+    List,
+    /// Submit a manuscript file as a new job
+    Submit {
+        file: PathBuf,
+    },
+    /// Cancel a running job
+    Cancel {
+        id: String,
+    },
+    /// Show the exceptions a job has reported
+    Exceptions {
+        id: String,
+    },
+}
 
 #[tokio::main]
 async fn main() -> io::Result<()> {
+    let args = Args::parse();
     env_logger::init();
 
+    let config = match config::Config::load(args.config, config::Overrides {
+        metadata_url: args.metadata_url,
+        image: args.image,
+        container_name: args.container_name,
+        sql_port: args.sql_port,
+        job_port: args.job_port,
+        auto_ports: args.auto_ports.then_some(true),
+        table_sort: args.table_sort,
+        demo: args.demo.then_some(true),
+        profile: args.profile,
+        workspace: args.workspace,
+    }) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let metadata_options = metadata::MetadataOptions {
+        url: config.metadata_url.value.clone(),
+        offline: args.offline,
+        fixture: args.fixture,
+    };
+    match args.command {
+        Some(Command::Validate { files }) => {
+            let code = run_validate(files, &config, &metadata_options).await;
+            std::process::exit(code);
+        }
+        Some(Command::Jobs { command }) => {
+            let code = run_jobs(command, &config).await;
+            std::process::exit(code);
+        }
+        None => {}
+    }
+
     let mut terminal = ratatui::init();
-    let mut app = app::App::new().await;
+    // Ctrl+Enter is only reported by terminals that support the kitty keyboard protocol
+    let keyboard_enhanced = supports_keyboard_enhancement().unwrap_or(false);
+    if keyboard_enhanced {
+        execute!(
+            io::stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+    }
+    let mut app = app::App::new(config, metadata_options).await;
+    app.keyboard_enhanced = keyboard_enhanced;
     let app_result = app.run(&mut terminal);
+    if keyboard_enhanced {
+        execute!(io::stdout(), PopKeyboardEnhancementFlags)?;
+    }
     ratatui::restore();
     app_result
 }
+
+// `ms validate`：诊断输出到 stdout，汇总和错误输出到 stderr
+async fn run_validate(files: Vec<PathBuf>, config: &config::Config, metadata_options: &metadata::MetadataOptions) -> i32 {
+    let (chains, source) = app::App::fetch_chains(metadata_options).await;
+    if let metadata::ChainsSource::Unavailable(reason) = &source {
+        eprintln!("Chain metadata unavailable: {}", reason);
+        return 2;
+    }
+    let catalog = catalog::Catalog::build(&chains, config.table_sort.value);
+
+    let files = if files.is_empty() {
+        let mut browser = manuscript::ManuscriptBrowser::new(config.workspace.value.clone(), catalog.clone());
+        browser.scan();
+        if let Some(error) = browser.error {
+            eprintln!("{}", error);
+            return 2;
+        }
+        browser.files.into_iter().map(|file| file.path).collect()
+    } else {
+        files
+    };
+
+    let (mut errors, mut warnings) = (0, 0);
+    for path in &files {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                errors += 1;
+                continue;
+            }
+        };
+        for diagnostic in validate::validate(path, &content, &catalog) {
+            match diagnostic.severity {
+                validate::Severity::Error => errors += 1,
+                validate::Severity::Warning => warnings += 1,
+            }
+            println!("{}", diagnostic);
+        }
+    }
+    eprintln!("Checked {} file(s): {} error(s), {} warning(s)", files.len(), errors, warnings);
+    if errors > 0 { 1 } else { 0 }
+}
+
+// `ms jobs`：使用当前环境的 job manager 端口
+async fn run_jobs(command: JobsCommand, config: &config::Config) -> i32 {
+    let Some(profile) = config.profiles.iter().find(|profile| profile.name == config.profile.value) else {
+        eprintln!("Unknown profile '{}'", config.profile.value);
+        return 2;
+    };
+    let client = jobs::JobClient::new(&docker::DockerManager::new(profile, config).job_endpoint());
+
+    let result = match command {
+        JobsCommand::List => client.jobs().await.map(|jobs| {
+            if jobs.is_empty() {
+                eprintln!("No jobs on {}", client.base_url());
+            }
+            for job in jobs {
+                println!("{:<32}  {:<12}  {:<19}  {:>11}  {}", job.id, job.state, job.started(), job.elapsed(), job.name);
+            }
+        }),
+        JobsCommand::Submit { file } => {
+            let content = match std::fs::read_to_string(&file) {
+                Ok(content) => content,
+                Err(e) => {
+                    eprintln!("{}: {}", file.display(), e);
+                    return 1;
+                }
+            };
+            let manuscript = match serde_yaml::from_str::<manuscript::Manuscript>(&content) {
+                Ok(manuscript) => manuscript,
+                Err(e) => {
+                    eprintln!("{}: {}", file.display(), e);
+                    return 1;
+                }
+            };
+            client.submit(&content, manuscript.parallelism).await.map(|id| {
+                eprintln!("Submitted {} to {}", file.display(), client.base_url());
+                println!("{}", id);
+            })
+        }
+        JobsCommand::Cancel { id } => client.cancel(&id).await.map(|()| {
+            eprintln!("Cancellation requested for job {}", id);
+        }),
+        JobsCommand::Exceptions { id } => client.exceptions(&id).await.map(|exceptions| {
+            if exceptions.is_empty() {
+                eprintln!("Job {} has no exceptions", id);
+            }
+            for line in exceptions.lines() {
+                println!("{}", line);
+            }
+        }),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
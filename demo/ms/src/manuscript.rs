@@ -0,0 +1,444 @@
+use std::{cell::Cell, fs, io, path::{Path, PathBuf}};
+use serde::Deserialize;
+use crate::catalog::Catalog;
+use crate::editor::TextEditor;
+use crate::validate::{self, Diagnostic, Severity};
+
+pub const MANUSCRIPT_FILE: &str = "manuscript.yaml";
+// 扫描工作区时最多进入的目录层数
+const MAX_SCAN_DEPTH: usize = 3;
+
+// manuscript.yaml 的结构，只解析浏览和校验需要的字段
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manuscript {
+    #[serde(default)]
+    pub name: String,
+    pub spec_version: Option<String>,
+    pub parallelism: Option<u32>,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    #[serde(default)]
+    pub sinks: Vec<Sink>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Source {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub dataset: Option<String>,
+    pub filter: Option<String>,
+}
+
+impl Source {
+    // dataset 的格式为 <chain>.<table>
+    pub fn chain_table(&self) -> Option<(&str, &str)> {
+        self.dataset.as_deref()?
+            .split_once('.')
+            .filter(|(chain, table)| !chain.is_empty() && !table.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Transform {
+    pub name: String,
+    #[serde(default)]
+    pub sql: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Sink {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub from: Option<String>,
+    pub database: Option<String>,
+    pub schema: Option<String>,
+    pub table: Option<String>,
+}
+
+impl Sink {
+    // "postgres → zkevm.public.blocks"
+    pub fn describe(&self) -> String {
+        let target: Vec<&str> = [&self.database, &self.schema, &self.table]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        if target.is_empty() {
+            self.kind.clone()
+        } else {
+            format!("{} → {}", self.kind, target.join("."))
+        }
+    }
+}
+
+// 工作区中的一个 manuscript 文件
+#[derive(Debug, Clone)]
+pub struct ManuscriptFile {
+    pub path: PathBuf,
+    pub content: String,
+    pub manuscript: Result<Manuscript, String>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ManuscriptFile {
+    pub fn load(path: &Path, catalog: &Catalog) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let manuscript = serde_yaml::from_str(&content).map_err(|e| e.to_string());
+        let diagnostics = validate::validate(path, &content, catalog);
+        Ok(Self { path: path.to_path_buf(), content, manuscript, diagnostics })
+    }
+
+    // 解析失败时用文件名（manuscript.yaml 则用所在目录名）
+    pub fn name(&self) -> String {
+        match &self.manuscript {
+            Ok(manuscript) if !manuscript.name.is_empty() => manuscript.name.clone(),
+            _ => {
+                let stem = if self.path.file_name().is_some_and(|name| name == MANUSCRIPT_FILE) {
+                    self.path.parent().and_then(Path::file_name)
+                } else {
+                    self.path.file_stem()
+                };
+                stem.map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+            }
+        }
+    }
+
+    pub fn chain(&self) -> Option<&str> {
+        self.manuscript.as_ref().ok()?
+            .sources.iter()
+            .find_map(|source| source.chain_table().map(|(chain, _)| chain))
+    }
+
+    pub fn source_tables(&self) -> Vec<&str> {
+        self.manuscript.as_ref()
+            .map(|manuscript| manuscript.sources.iter().filter_map(|source| source.dataset.as_deref()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn sink(&self) -> Option<String> {
+        let manuscript = self.manuscript.as_ref().ok()?;
+        let sinks: Vec<String> = manuscript.sinks.iter().map(Sink::describe).collect();
+        (!sinks.is_empty()).then(|| sinks.join(", "))
+    }
+
+    pub fn errors(&self) -> usize {
+        self.diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error).count()
+    }
+}
+
+// manuscript.yaml 固定视为 manuscript；其他 YAML 文件需要有顶层的 sources 或 specVersion
+fn is_manuscript(path: &Path) -> bool {
+    let is_yaml = path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml");
+    if !is_yaml {
+        return false;
+    }
+    if path.file_stem().is_some_and(|stem| stem == "manuscript") {
+        return true;
+    }
+    let Ok(content) = fs::read_to_string(path) else {
+        return false;
+    };
+    match serde_yaml::from_str::<serde_yaml::Value>(&content) {
+        Ok(serde_yaml::Value::Mapping(mapping)) => {
+            mapping.contains_key("sources") || mapping.contains_key("specVersion")
+        }
+        _ => false,
+    }
+}
+
+fn scan_dir(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            if depth < MAX_SCAN_DEPTH {
+                // 子目录读取失败不影响其他文件
+                let _ = scan_dir(&path, depth + 1, found);
+            }
+        } else if is_manuscript(&path) {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+// 名称同时用作目录名，只允许字母、数字、- 和 _
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Name must not be empty".to_string());
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid name '{}': use letters, digits, '-' and '_'", name));
+    }
+    Ok(())
+}
+
+// 生成 manuscript 时可选的 sink
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SinkType {
+    #[default]
+    Print,
+    Postgres,
+}
+
+impl SinkType {
+    pub const ALL: [SinkType; 2] = [SinkType::Print, SinkType::Postgres];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SinkType::Print => "print",
+            SinkType::Postgres => "postgres",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|sink| sink == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+// 生成完整的 manuscript：一个 dataset source、一个 SQL transform 和一个 sink。
+// transform 只能引用本文件中定义的名字，所以 SQL 中的 chain.table 换成 source 名
+pub fn generate(name: &str, chain: &str, table: &str, sql: &str, sink: SinkType) -> String {
+    let source = format!("{}_{}", chain, table);
+    let sql = sql.trim().replace(&format!("{}.{}", chain, table), &source);
+    let mut yaml = format!(
+        "name: {name}\n\
+         specVersion: v1.0.0\n\
+         parallelism: 1\n\
+         \n\
+         sources:\n  \
+           - name: {source}\n    \
+             type: dataset\n    \
+             dataset: {chain}.{table}\n\
+         \n\
+         transforms:\n  \
+           - name: {source}_transform\n    \
+             sql: |\n"
+    );
+    for line in sql.lines() {
+        yaml.push_str(&format!("      {}\n", line));
+    }
+    yaml.push_str(&format!(
+        "\n\
+         sinks:\n  \
+           - name: {source}_sink\n    \
+             type: {}\n    \
+             from: {source}_transform\n",
+        sink.as_str(),
+    ));
+    // postgres 的连接信息需要用户补充，账号密码从环境变量读取
+    if sink == SinkType::Postgres {
+        yaml.push_str(&format!(
+            "    database: {chain}\n    \
+                 schema: public\n    \
+                 table: {table}\n    \
+                 primary_key: id\n    \
+                 config:\n      \
+                   host: postgres\n      \
+                   port: 5432\n      \
+                   username: ${{env.POSTGRES_USER}}\n      \
+                   password: ${{env.POSTGRES_PASSWORD}}\n"
+        ));
+    }
+    yaml
+}
+
+// 新建 manuscript 的模板：读取一张表，原样打印
+pub fn template(name: &str, chain: &str, table: &str) -> String {
+    generate(name, chain, table, &format!("SELECT * FROM {}.{}", chain, table), SinkType::Print)
+}
+
+// 复制时替换顶层的 name 字段，保留其余内容和注释
+fn rename(content: &str, name: &str) -> String {
+    let mut renamed = false;
+    let mut lines: Vec<String> = content.lines()
+        .map(|line| {
+            if !renamed && line.starts_with("name:") {
+                renamed = true;
+                format!("name: {}", name)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !renamed {
+        lines.insert(0, format!("name: {}", name));
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+// 从选中的表和保存的 SQL 生成 manuscript：先选择名称和 sink，再在编辑器中检查和修改
+#[derive(Debug, Clone)]
+pub struct ManuscriptDraft {
+    pub chain: String,
+    pub table: String,
+    pub sql: String,
+    pub name: String,
+    pub sink: SinkType,
+    pub editor: Option<TextEditor>,  // 生成后才有，None 时处于选项步骤
+    pub message: Option<String>,
+}
+
+impl ManuscriptDraft {
+    pub fn new(chain: &str, table: &str, sql: &str) -> Self {
+        Self {
+            chain: chain.to_string(),
+            table: table.to_string(),
+            sql: sql.to_string(),
+            name: format!("{}_{}", chain, table),
+            sink: SinkType::default(),
+            editor: None,
+            message: None,
+        }
+    }
+
+    pub fn generate(&mut self) -> Result<(), String> {
+        check_name(&self.name)?;
+        let yaml = generate(&self.name, &self.chain, &self.table, &self.sql, self.sink);
+        self.editor = Some(TextEditor::new(yaml));
+        self.message = None;
+        Ok(())
+    }
+
+    // 按编辑器中的当前内容校验
+    pub fn diagnostics(&self, catalog: &Catalog) -> Vec<Diagnostic> {
+        let Some(editor) = &self.editor else {
+            return Vec::new();
+        };
+        let path = PathBuf::from(&self.name).join(MANUSCRIPT_FILE);
+        validate::validate(&path, &editor.text, catalog)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptAction {
+    Create,
+    Duplicate,
+}
+
+// 输入新 manuscript 名称的提示框
+#[derive(Debug, Clone)]
+pub struct NamePrompt {
+    pub action: PromptAction,
+    pub input: String,
+}
+
+// MANUSCRIPTS 页：工作区中的 manuscript 列表和文件查看
+#[derive(Debug, Clone, Default)]
+pub struct ManuscriptBrowser {
+    pub dir: PathBuf,
+    catalog: Catalog,               // 校验用的数据字典
+    pub files: Vec<ManuscriptFile>,
+    pub error: Option<String>,      // 工作区扫描失败的原因
+    pub selected: usize,
+    pub viewing: bool,              // 焦点在右侧的文件内容上
+    pub scroll: usize,
+    pub prompt: Option<NamePrompt>,
+    pub confirm_delete: bool,
+    pub message: Option<String>,
+    pub height: Cell<usize>,        // 上一次渲染时文件内容的可见行数
+}
+
+impl ManuscriptBrowser {
+    pub fn new(dir: PathBuf, catalog: Catalog) -> Self {
+        Self { dir, catalog, ..Self::default() }
+    }
+
+    // 重新扫描工作区，尽量保持当前选中的文件
+    pub fn scan(&mut self) {
+        let selected = self.selected_file().map(|file| file.path.clone());
+        let mut paths = Vec::new();
+        self.error = match scan_dir(&self.dir, 1, &mut paths) {
+            Ok(()) => None,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => Some(format!("Failed to read {}: {}", self.dir.display(), e)),
+        };
+        paths.sort();
+        self.files = paths.iter().filter_map(|path| ManuscriptFile::load(path, &self.catalog).ok()).collect();
+        self.select_path(selected.as_deref());
+    }
+
+    fn select_path(&mut self, path: Option<&Path>) {
+        if let Some(index) = path.and_then(|path| self.files.iter().position(|file| file.path == path)) {
+            self.selected = index;
+        }
+        self.selected = self.selected.min(self.files.len().saturating_sub(1));
+    }
+
+    pub fn selected_file(&self) -> Option<&ManuscriptFile> {
+        self.files.get(self.selected)
+    }
+
+    pub fn up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.scroll = 0;
+        }
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.files.len() {
+            self.selected += 1;
+            self.scroll = 0;
+        }
+    }
+
+    pub fn scroll_up(&mut self, amount: usize) {
+        self.scroll = self.scroll.saturating_sub(amount);
+    }
+
+    pub fn scroll_down(&mut self, amount: usize) {
+        let lines = self.selected_file().map(|file| file.content.lines().count()).unwrap_or(0);
+        let max = lines.saturating_sub(self.height.get());
+        self.scroll = (self.scroll + amount).min(max);
+    }
+
+    // 新建到 <workspace>/<name>/manuscript.yaml，与 manuscript-cli 的布局一致
+    pub fn create(&mut self, name: &str, content: &str) -> Result<PathBuf, String> {
+        check_name(name)?;
+        let dir = self.dir.join(name);
+        let path = dir.join(MANUSCRIPT_FILE);
+        if path.exists() {
+            return Err(format!("{} already exists", path.display()));
+        }
+        fs::create_dir_all(&dir)
+            .and_then(|_| fs::write(&path, content))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        self.scan();
+        self.select_path(Some(&path));
+        Ok(path)
+    }
+
+    pub fn duplicate(&mut self, name: &str) -> Result<PathBuf, String> {
+        let content = self.selected_file()
+            .map(|file| rename(&file.content, name))
+            .ok_or_else(|| "No manuscript selected".to_string())?;
+        self.create(name, &content)
+    }
+
+    // 删除文件；manuscript 所在的子目录变空时一并删除
+    pub fn delete(&mut self) -> Result<PathBuf, String> {
+        let path = self.selected_file()
+            .map(|file| file.path.clone())
+            .ok_or_else(|| "No manuscript selected".to_string())?;
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+        if let Some(parent) = path.parent().filter(|parent| *parent != self.dir) {
+            if fs::read_dir(parent).is_ok_and(|mut entries| entries.next().is_none()) {
+                let _ = fs::remove_dir(parent);
+            }
+        }
+        self.scan();
+        Ok(path)
+    }
+}
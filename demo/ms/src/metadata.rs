@@ -0,0 +1,149 @@
+use std::{fs, path::PathBuf, time::Duration};
+use chrono::{DateTime, Local, Utc};
+use reqwest::{header, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub const NETWORK_CHAINS_URL: &str = "https://api.chainbase.com/api/v1/metadata/network_chains";
+
+// 网络不通时尽快退回缓存，不要卡在界面出现之前
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Default)]
+pub struct MetadataOptions {
+    pub url: String,
+    pub offline: bool,
+    pub fixture: Option<PathBuf>,
+}
+
+// 链元数据的来源，用于在界面上提示数据是否过期
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainsSource {
+    Live,
+    Cache { fetched_at: DateTime<Utc>, reason: String },
+    Fixture(PathBuf),
+    Unavailable(String),
+}
+
+impl ChainsSource {
+    pub fn banner(&self) -> Option<String> {
+        match self {
+            ChainsSource::Live => None,
+            ChainsSource::Cache { fetched_at, reason } => Some(format!(
+                "{} - chain metadata stale since {}",
+                reason,
+                fetched_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            )),
+            ChainsSource::Fixture(path) => Some(format!("Chain metadata loaded from {}", path.display())),
+            ChainsSource::Unavailable(error) => Some(format!("No chain metadata available: {}", error)),
+        }
+    }
+}
+
+// 磁盘缓存：最后一次成功的响应以及获取时间和 ETag
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedResponse {
+    fetched_at: DateTime<Utc>,
+    etag: Option<String>,
+    body: String,
+}
+
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("ms").join("network_chains.json"))
+}
+
+fn read_cache() -> Option<CachedResponse> {
+    let content = fs::read_to_string(cache_path()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_cache(cached: &CachedResponse) -> Result<(), String> {
+    let path = cache_path().ok_or("No cache directory available")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string(cached).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+// 按 fixture → 离线缓存 → 在线请求（失败时回退到缓存）的顺序加载
+pub async fn load<T: DeserializeOwned>(options: &MetadataOptions) -> (Option<T>, ChainsSource) {
+    if let Some(fixture) = &options.fixture {
+        let parsed = fs::read_to_string(fixture)
+            .map_err(|e| e.to_string())
+            .and_then(|body| serde_json::from_str(&body).map_err(|e| e.to_string()));
+        return match parsed {
+            Ok(data) => (Some(data), ChainsSource::Fixture(fixture.clone())),
+            Err(e) => (None, ChainsSource::Unavailable(format!("{}: {}", fixture.display(), e))),
+        };
+    }
+
+    let cached = read_cache();
+    if options.offline {
+        return from_cache(cached, "Offline mode".to_string());
+    }
+
+    match fetch(&options.url, cached.as_ref()).await {
+        Ok(fresh) => match serde_json::from_str(&fresh.body) {
+            Ok(data) => {
+                if let Err(e) = write_cache(&fresh) {
+                    log::warn!("Failed to write metadata cache: {}", e);
+                }
+                (Some(data), ChainsSource::Live)
+            }
+            Err(e) => from_cache(cached, format!("Invalid response ({})", e)),
+        },
+        Err(e) => from_cache(cached, e),
+    }
+}
+
+fn from_cache<T: DeserializeOwned>(cached: Option<CachedResponse>, reason: String) -> (Option<T>, ChainsSource) {
+    let Some(cached) = cached else {
+        return (None, ChainsSource::Unavailable(reason));
+    };
+    match serde_json::from_str(&cached.body) {
+        Ok(data) => (Some(data), ChainsSource::Cache { fetched_at: cached.fetched_at, reason }),
+        Err(e) => (None, ChainsSource::Unavailable(format!("{}; cache unreadable: {}", reason, e))),
+    }
+}
+
+async fn fetch(url: &str, cached: Option<&CachedResponse>) -> Result<CachedResponse, String> {
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let mut request = client.get(url);
+    if let Some(etag) = cached.and_then(|c| c.etag.as_ref()) {
+        request = request.header(header::IF_NONE_MATCH, etag);
+    }
+
+    let response = request.send().await.map_err(|e| format!("Request failed ({})", e))?;
+    let status = response.status();
+
+    // 304: 缓存仍然有效，只刷新获取时间
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            return Ok(CachedResponse {
+                fetched_at: Utc::now(),
+                etag: cached.etag.clone(),
+                body: cached.body.clone(),
+            });
+        }
+    }
+    if !status.is_success() {
+        return Err(format!("Server returned {}", status));
+    }
+
+    let etag = response.headers()
+        .get(header::ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let body = response.text().await.map_err(|e| format!("Request failed ({})", e))?;
+
+    Ok(CachedResponse {
+        fetched_at: Utc::now(),
+        etag,
+        body,
+    })
+}
@@ -0,0 +1,234 @@
+use std::{collections::VecDeque, time::Instant};
+use serde::Deserialize;
+use crate::jobs::JobSummary;
+
+// 每个作业保留的采样数，按 2 秒一次约 4 分钟
+pub const HISTORY_LEN: usize = 120;
+
+// subtasks/metrics 请求的指标，按所有 subtask 聚合
+pub const VERTEX_METRICS: [&str; 3] = ["numRecordsInPerSecond", "numRecordsOutPerSecond", "backPressuredTimeMsPerSecond"];
+
+// /jobs/:id 中的一个算子
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vertex {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub parallelism: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobDetail {
+    #[serde(default)]
+    pub vertices: Vec<Vertex>,
+}
+
+// subtasks/metrics 返回的一项聚合值
+#[derive(Debug, Clone, Deserialize)]
+pub struct AggregatedMetric {
+    pub id: String,
+    #[serde(default)]
+    pub max: f64,
+    #[serde(default)]
+    pub sum: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VertexMetrics {
+    pub name: String,
+    pub parallelism: u32,
+    pub records_in: f64,    // 所有 subtask 之和，条/秒
+    pub records_out: f64,
+    pub backpressure: f64,  // 最忙的 subtask 被反压的时间比例，0..1
+}
+
+impl VertexMetrics {
+    pub fn new(vertex: &Vertex, metrics: &[AggregatedMetric]) -> Self {
+        let get = |id: &str| metrics.iter().find(|metric| metric.id == id);
+        Self {
+            name: vertex.name.clone(),
+            parallelism: vertex.parallelism,
+            records_in: get("numRecordsInPerSecond").map(|metric| metric.sum).unwrap_or_default(),
+            records_out: get("numRecordsOutPerSecond").map(|metric| metric.sum).unwrap_or_default(),
+            backpressure: get("backPressuredTimeMsPerSecond")
+                .map(|metric| (metric.max / 1000.0).clamp(0.0, 1.0))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CheckpointCounts {
+    #[serde(default)]
+    pub completed: u64,
+    #[serde(default)]
+    pub failed: u64,
+    #[serde(default)]
+    pub in_progress: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompletedCheckpoint {
+    pub id: u64,
+    #[serde(default)]
+    pub end_to_end_duration: u64,  // 毫秒
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FailedCheckpoint {
+    pub id: u64,
+    pub failure_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LatestCheckpoints {
+    pub completed: Option<CompletedCheckpoint>,
+    pub failed: Option<FailedCheckpoint>,
+}
+
+// /jobs/:id/checkpoints
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CheckpointStats {
+    #[serde(default)]
+    pub counts: CheckpointCounts,
+    #[serde(default)]
+    pub latest: LatestCheckpoints,
+}
+
+// 一次轮询得到的作业指标
+#[derive(Debug, Clone, Default)]
+pub struct JobMetrics {
+    pub vertices: Vec<VertexMetrics>,
+    pub checkpoints: CheckpointStats,
+}
+
+impl JobMetrics {
+    // 算子按拓扑顺序排列：第一个是 source，最后一个是 sink
+    pub fn records_in(&self) -> f64 {
+        self.vertices.first().map(|vertex| vertex.records_out).unwrap_or_default()
+    }
+
+    pub fn records_out(&self) -> f64 {
+        self.vertices.last().map(|vertex| vertex.records_in).unwrap_or_default()
+    }
+
+    pub fn backpressure(&self) -> f64 {
+        self.vertices.iter().map(|vertex| vertex.backpressure).fold(0.0, f64::max)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub at: f64,  // 打开面板后的秒数
+    pub records_in: f64,
+    pub records_out: f64,
+    pub backpressure: f64,
+    pub checkpoint_duration: Option<u64>,
+    pub checkpoints_failed: u64,
+}
+
+// 固定容量的环形缓冲，满了之后覆盖最旧的采样
+#[derive(Debug, Clone)]
+pub struct History {
+    samples: VecDeque<Sample>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self { samples: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    pub fn push(&mut self, sample: Sample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    // sparkline 只接受整数
+    pub fn series(&self, value: impl Fn(&Sample) -> f64) -> Vec<u64> {
+        self.samples.iter().map(|sample| value(sample).round().max(0.0) as u64).collect()
+    }
+
+    // 图表的 (时间, 毫秒)，没有完成的 checkpoint 的采样跳过
+    pub fn checkpoint_durations(&self) -> Vec<(f64, f64)> {
+        self.samples.iter()
+            .filter_map(|sample| Some((sample.at, sample.checkpoint_duration? as f64)))
+            .collect()
+    }
+
+    // 失败数增加的时刻
+    pub fn checkpoint_failures(&self) -> Vec<(f64, f64)> {
+        self.samples.iter()
+            .zip(self.samples.iter().skip(1))
+            .filter(|(previous, sample)| sample.checkpoints_failed > previous.checkpoints_failed)
+            .map(|(_, sample)| (sample.at, sample.checkpoint_duration.unwrap_or_default() as f64))
+            .collect()
+    }
+
+    // 图表的 x 轴范围
+    pub fn time_bounds(&self) -> [f64; 2] {
+        let first = self.samples.front().map(|sample| sample.at).unwrap_or_default();
+        let last = self.samples.back().map(|sample| sample.at).unwrap_or_default();
+        [first, last.max(first + 1.0)]
+    }
+}
+
+// 作业面板中单个作业的指标页
+#[derive(Debug, Clone)]
+pub struct JobDashboard {
+    pub job_id: String,
+    pub job_name: String,
+    pub opened_at: Instant,
+    pub metrics: Option<JobMetrics>,
+    pub error: Option<String>,
+    pub history: History,
+}
+
+impl JobDashboard {
+    pub fn new(job: &JobSummary) -> Self {
+        Self {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            opened_at: Instant::now(),
+            metrics: None,
+            error: None,
+            history: History::new(HISTORY_LEN),
+        }
+    }
+
+    // 失败时保留上一次的指标和历史
+    pub fn update(&mut self, result: Result<JobMetrics, String>) {
+        match result {
+            Ok(metrics) => {
+                self.history.push(Sample {
+                    at: self.opened_at.elapsed().as_secs_f64(),
+                    records_in: metrics.records_in(),
+                    records_out: metrics.records_out(),
+                    backpressure: metrics.backpressure(),
+                    checkpoint_duration: metrics.checkpoints.latest.completed.as_ref().map(|checkpoint| checkpoint.end_to_end_duration),
+                    checkpoints_failed: metrics.checkpoints.counts.failed,
+                });
+                self.metrics = Some(metrics);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+// 1234567.8 → "1.2M"
+pub fn format_rate(value: f64) -> String {
+    if value >= 1_000_000.0 {
+        format!("{:.1}M", value / 1_000_000.0)
+    } else if value >= 1_000.0 {
+        format!("{:.1}k", value / 1_000.0)
+    } else {
+        format!("{:.1}", value)
+    }
+}
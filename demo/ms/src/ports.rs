@@ -0,0 +1,115 @@
+use std::{fmt, io, net::TcpListener};
+
+// 向上查找空闲端口的范围
+const PORT_SEARCH_RANGE: u16 = 200;
+
+// 调试容器映射到宿主机的端口
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ports {
+    pub sql: u16,
+    pub job: u16,
+}
+
+impl fmt::Display for Ports {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SQL {}, jobs {}", self.sql, self.job)
+    }
+}
+
+// 被其他程序占用的端口
+#[derive(Debug, Clone)]
+pub struct PortConflict {
+    pub port: u16,
+    pub holder: Option<String>,  // "name (pid N)"，查不到时为 None
+}
+
+impl fmt::Display for PortConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.holder {
+            Some(holder) => write!(f, "port {} is in use by {}", self.port, holder),
+            None => write!(f, "port {} is already in use", self.port),
+        }
+    }
+}
+
+// docker 会在 0.0.0.0 上监听，所以在同样的地址上试绑定
+fn is_free(port: u16) -> bool {
+    TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+pub fn conflicts(ports: Ports) -> Vec<PortConflict> {
+    [ports.sql, ports.job]
+        .into_iter()
+        .filter(|port| !is_free(*port))
+        .map(|port| PortConflict { port, holder: holder(port).ok().flatten() })
+        .collect()
+}
+
+// 从配置的端口开始向上找空闲端口，找不到时让系统分配
+pub fn pick_free(ports: Ports) -> io::Result<Ports> {
+    let sql = find_free(ports.sql, None)?;
+    let job = find_free(ports.job, Some(sql))?;
+    Ok(Ports { sql, job })
+}
+
+fn find_free(start: u16, taken: Option<u16>) -> io::Result<u16> {
+    let candidate = (start..start.saturating_add(PORT_SEARCH_RANGE))
+        .find(|port| Some(*port) != taken && is_free(*port));
+    match candidate {
+        Some(port) => Ok(port),
+        None => Ok(TcpListener::bind(("0.0.0.0", 0))?.local_addr()?.port()),
+    }
+}
+
+// 通过 /proc 找到监听该端口的进程
+#[cfg(target_os = "linux")]
+fn holder(port: u16) -> io::Result<Option<String>> {
+    use std::fs;
+
+    let mut inodes = Vec::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(content) = fs::read_to_string(table) else {
+            continue;
+        };
+        for line in content.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // local_address 为 "ADDR:PORT"（十六进制），状态 0A 表示 LISTEN
+            let listening = fields.get(3) == Some(&"0A");
+            let local_port = fields.get(1)
+                .and_then(|address| address.rsplit(':').next())
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok());
+            if listening && local_port == Some(port) {
+                if let Some(inode) = fields.get(9) {
+                    inodes.push(format!("socket:[{}]", inode));
+                }
+            }
+        }
+    }
+    if inodes.is_empty() {
+        return Ok(None);
+    }
+
+    for entry in fs::read_dir("/proc")?.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        // 其他用户的进程通常没有权限读取
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let holds_port = fds.flatten().any(|fd| {
+            fs::read_link(fd.path())
+                .is_ok_and(|target| inodes.iter().any(|inode| target.as_os_str() == inode.as_str()))
+        });
+        if holds_port {
+            let name = fs::read_to_string(entry.path().join("comm")).unwrap_or_default();
+            return Ok(Some(format!("{} (pid {})", name.trim(), pid)));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn holder(_port: u16) -> io::Result<Option<String>> {
+    Ok(None)
+}
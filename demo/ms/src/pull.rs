@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+use indexmap::IndexMap;
+
+// 下载占每层进度的 80%，解压占 20%
+const DOWNLOAD_WEIGHT: f64 = 0.8;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum LayerState {
+    #[default]
+    Waiting,
+    Downloading,
+    Downloaded,
+    Extracting,
+    Complete,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LayerProgress {
+    pub state: LayerState,
+    pub current: u64,
+    pub total: u64,
+}
+
+impl LayerProgress {
+    fn fraction(&self) -> f64 {
+        let partial = if self.total > 0 {
+            (self.current as f64 / self.total as f64).min(1.0)
+        } else {
+            0.0
+        };
+        match self.state {
+            LayerState::Waiting => 0.0,
+            LayerState::Downloading => partial * DOWNLOAD_WEIGHT,
+            LayerState::Downloaded => DOWNLOAD_WEIGHT,
+            LayerState::Extracting => DOWNLOAD_WEIGHT + partial * (1.0 - DOWNLOAD_WEIGHT),
+            LayerState::Complete => 1.0,
+        }
+    }
+}
+
+// 镜像拉取的逐层进度
+#[derive(Debug, Clone)]
+pub struct PullProgress {
+    pub layers: IndexMap<String, LayerProgress>,
+    started: Instant,
+}
+
+impl Default for PullProgress {
+    fn default() -> Self {
+        Self {
+            layers: IndexMap::new(),
+            started: Instant::now(),
+        }
+    }
+}
+
+impl PullProgress {
+    // Apply one status event; returns false when it isn't about a layer
+    pub fn update(&mut self, id: &str, status: &str, current: Option<u64>, total: Option<u64>) -> bool {
+        let state = match status {
+            "Pulling fs layer" | "Waiting" => LayerState::Waiting,
+            "Downloading" => LayerState::Downloading,
+            "Verifying Checksum" | "Download complete" => LayerState::Downloaded,
+            "Extracting" => LayerState::Extracting,
+            "Pull complete" | "Already exists" => LayerState::Complete,
+            _ => return false,
+        };
+
+        let layer = self.layers.entry(id.to_string()).or_default();
+        // 解压阶段的字节数和下载阶段不同，不覆盖已下载的大小
+        if state == LayerState::Downloading {
+            if let Some(total) = total {
+                layer.total = total;
+            }
+            if let Some(current) = current {
+                layer.current = current;
+            }
+        } else if state == LayerState::Downloaded || state == LayerState::Complete {
+            layer.current = layer.total;
+        }
+        layer.state = state;
+        true
+    }
+
+    pub fn fraction(&self) -> f64 {
+        if self.layers.is_empty() {
+            return 0.0;
+        }
+        self.layers.values().map(LayerProgress::fraction).sum::<f64>() / self.layers.len() as f64
+    }
+
+    pub fn downloaded_bytes(&self) -> u64 {
+        self.layers.values().map(|layer| layer.current.min(layer.total)).sum()
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.layers.values().map(|layer| layer.total).sum()
+    }
+
+    pub fn extracting(&self) -> usize {
+        self.layers.values().filter(|layer| layer.state == LayerState::Extracting).count()
+    }
+
+    pub fn complete(&self) -> usize {
+        self.layers.values().filter(|layer| layer.state == LayerState::Complete).count()
+    }
+
+    // 根据平均下载速度估算剩余时间
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total_bytes();
+        let downloaded = self.downloaded_bytes();
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if total == 0 || downloaded == 0 || elapsed < 1.0 {
+            return None;
+        }
+        let rate = downloaded as f64 / elapsed;
+        Some(Duration::from_secs_f64(total.saturating_sub(downloaded) as f64 / rate))
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1_000_000_000 {
+        format!("{:.2} GB", bytes as f64 / 1e9)
+    } else if bytes >= 1_000_000 {
+        format!("{:.1} MB", bytes as f64 / 1e6)
+    } else {
+        format!("{:.1} kB", bytes as f64 / 1e3)
+    }
+}
@@ -0,0 +1,370 @@
+use std::{fmt, time::Duration};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use crate::app::Column;
+
+// 单次请求的超时；statement 协议的每一页都是一次短请求
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Trino/Presto 客户端协议的 stats 字段
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryStats {
+    #[serde(default)]
+    pub state: String,
+    #[serde(default)]
+    pub processed_rows: u64,
+    #[serde(default)]
+    pub processed_bytes: u64,
+    #[serde(default)]
+    pub elapsed_time_millis: u64,
+}
+
+impl QueryStats {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} | {} rows | {} | {:.1}s",
+            self.state,
+            self.processed_rows,
+            format_bytes(self.processed_bytes),
+            self.elapsed_time_millis as f64 / 1000.0
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLocation {
+    pub line_number: u32,
+    pub column_number: u32,
+}
+
+// 查询错误，transport 错误没有 error_name 和 location
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryError {
+    #[serde(default)]
+    pub message: String,
+    pub error_name: Option<String>,
+    pub error_location: Option<ErrorLocation>,
+}
+
+impl QueryError {
+    pub fn transport(message: String) -> Self {
+        Self {
+            message,
+            error_name: None,
+            error_location: None,
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = &self.error_name {
+            write!(f, "{}", name)?;
+            if let Some(location) = &self.error_location {
+                write!(f, " at line {}, column {}", location.line_number, location.column_number)?;
+            }
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SqlClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl SqlClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    // POST 语句后沿着 nextUri 逐页拉取，每一页都通过 sender 发送给 UI
+    pub async fn execute(&self, sql: &str, sender: mpsc::Sender<Result<Value, String>>) {
+        let mut page = self.statement_request(sql);
+
+        loop {
+            let document = match Self::fetch(page).await {
+                Ok(document) => document,
+                Err(e) => {
+                    let _ = sender.send(Err(e)).await;
+                    return;
+                }
+            };
+
+            let next_uri = next_uri(&document);
+
+            // UI 已经关闭接收端时停止拉取
+            if sender.send(Ok(document)).await.is_err() {
+                return;
+            }
+
+            match next_uri {
+                Some(uri) => page = self.page_request(&uri),
+                None => return,
+            }
+        }
+    }
+
+    // 拉取全部分页，返回列和所有行
+    pub async fn query(&self, sql: &str) -> Result<(Vec<Column>, Vec<Vec<Value>>), QueryError> {
+        let mut columns = Vec::new();
+        let mut rows = Vec::new();
+        let mut page = self.statement_request(sql);
+
+        loop {
+            let document = Self::fetch(page).await.map_err(QueryError::transport)?;
+            if let Some(error) = document.get("error") {
+                return Err(serde_json::from_value(error.clone())
+                    .unwrap_or_else(|_| QueryError::transport(error.to_string())));
+            }
+            if columns.is_empty() {
+                columns = parse_columns(&document);
+            }
+            if let Some(data) = document.get("data").and_then(|d| d.as_array()) {
+                rows.extend(data.iter().filter_map(|row| row.as_array().cloned()));
+            }
+
+            match next_uri(&document) {
+                Some(uri) => page = self.page_request(&uri),
+                None => return Ok((columns, rows)),
+            }
+        }
+    }
+
+    fn statement_request(&self, sql: &str) -> reqwest::RequestBuilder {
+        self.http
+            .post(format!("{}/v1/statement", self.base_url))
+            .header("X-Trino-User", "manuscript")
+            .body(sql.to_string())
+    }
+
+    fn page_request(&self, uri: &str) -> reqwest::RequestBuilder {
+        self.http
+            .get(uri)
+            .header("X-Trino-User", "manuscript")
+    }
+
+    async fn fetch(request: reqwest::RequestBuilder) -> Result<Value, String> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach SQL endpoint: {}", e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Server returned {}: {}", status, body.trim()));
+        }
+
+        response.json::<Value>()
+            .await
+            .map_err(|e| format!("Invalid response: {}", e))
+    }
+}
+
+fn next_uri(document: &Value) -> Option<String> {
+    document.get("nextUri")
+        .and_then(|uri| uri.as_str())
+        .map(|uri| uri.to_string())
+}
+
+pub fn parse_columns(document: &Value) -> Vec<Column> {
+    document.get("columns")
+        .and_then(|c| c.as_array())
+        .map(|columns| columns.iter()
+            .filter_map(|col| {
+                Some(Column {
+                    name: col.get("name")?.as_str()?.to_string(),
+                    type_: col.get("type")?.as_str()?.to_string(),
+                })
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Request {
+        method: String,
+        path: String,
+        user: Option<String>,
+        body: String,
+    }
+
+    // 本地 statement 协议桩：handler 拿到请求和 base url，返回响应 JSON
+    struct StubServer {
+        base_url: String,
+        requests: Arc<Mutex<Vec<Request>>>,
+    }
+
+    impl StubServer {
+        fn start(handler: fn(&Request, &str) -> String) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base_url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let (recorded, url) = (requests.clone(), base_url.clone());
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else {
+                        break;
+                    };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let mut parts = line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let path = parts.next().unwrap_or_default().to_string();
+
+                    let (mut length, mut user) = (0, None);
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                        if let Some((name, value)) = header.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                length = value.trim().parse().unwrap();
+                            } else if name.eq_ignore_ascii_case("x-trino-user") {
+                                user = Some(value.trim().to_string());
+                            }
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+
+                    let request = Request { method, path, user, body: String::from_utf8(body).unwrap() };
+                    let response = handler(&request, &url);
+                    recorded.lock().unwrap().push(request);
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        response.len(), response
+                    ).unwrap();
+                }
+            });
+            Self { base_url, requests }
+        }
+
+        fn requests(&self) -> Vec<Request> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    // 第一页只有列，第二页有数据，第三页结束
+    fn pages(request: &Request, base_url: &str) -> String {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("POST", "/v1/statement") => format!(
+                r#"{{"id": "q1", "nextUri": "{}/v1/statement/q1/1",
+                    "columns": [{{"name": "block_number", "type": "bigint"}}, {{"name": "hash", "type": "varchar"}}],
+                    "data": [[1, "0xa"]], "stats": {{"state": "RUNNING"}}}}"#,
+                base_url
+            ),
+            ("GET", "/v1/statement/q1/1") => format!(
+                r#"{{"id": "q1", "nextUri": "{}/v1/statement/q1/2", "data": [[2, "0xb"], [3, "0xc"]]}}"#,
+                base_url
+            ),
+            ("GET", "/v1/statement/q1/2") => r#"{"id": "q1", "stats": {"state": "FINISHED"}}"#.to_string(),
+            _ => r#"{"error": {"message": "unexpected request"}}"#.to_string(),
+        }
+    }
+
+    fn failing_pages(request: &Request, base_url: &str) -> String {
+        match request.path.as_str() {
+            "/v1/statement/q1/2" => r#"{"id": "q1", "stats": {"state": "FAILED"}, "error": {
+                "message": "line 1:8: Column 'blok_number' cannot be resolved",
+                "errorName": "COLUMN_NOT_FOUND",
+                "errorLocation": {"lineNumber": 1, "columnNumber": 8}
+            }}"#.to_string(),
+            _ => pages(request, base_url),
+        }
+    }
+
+    #[tokio::test]
+    async fn query_follows_next_uri_and_accumulates_rows() {
+        let server = StubServer::start(pages);
+        let (columns, rows) = SqlClient::new(&server.base_url).query("SELECT * FROM ethereum.blocks").await.unwrap();
+
+        let names: Vec<&str> = columns.iter().map(|column| column.name.as_str()).collect();
+        assert_eq!(names, ["block_number", "hash"]);
+        assert_eq!(columns[0].type_, "bigint");
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2], vec![Value::from(3), Value::from("0xc")]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].body, "SELECT * FROM ethereum.blocks");
+        assert!(requests.iter().all(|request| request.user.as_deref() == Some("manuscript")));
+    }
+
+    #[tokio::test]
+    async fn query_surfaces_the_error_page() {
+        let server = StubServer::start(failing_pages);
+        let error = SqlClient::new(&server.base_url).query("SELECT blok_number FROM ethereum.blocks").await.unwrap_err();
+
+        assert_eq!(error.error_name.as_deref(), Some("COLUMN_NOT_FOUND"));
+        assert_eq!(error.to_string(), "COLUMN_NOT_FOUND at line 1, column 8: line 1:8: Column 'blok_number' cannot be resolved");
+    }
+
+    #[tokio::test]
+    async fn execute_sends_every_page_up_to_the_error() {
+        let server = StubServer::start(failing_pages);
+        let (sender, mut receiver) = mpsc::channel(10);
+        SqlClient::new(&server.base_url).execute("SELECT blok_number FROM ethereum.blocks", sender).await;
+
+        let mut documents = Vec::new();
+        while let Some(page) = receiver.recv().await {
+            documents.push(page.unwrap());
+        }
+        assert_eq!(documents.len(), 3);
+        assert_eq!(parse_columns(&documents[0]).len(), 2);
+        let rows: usize = documents.iter().filter_map(|document| document["data"].as_array()).map(Vec::len).sum();
+        assert_eq!(rows, 3);
+        assert_eq!(documents[2]["error"]["errorName"], "COLUMN_NOT_FOUND");
+        assert!(server.requests().iter().all(|request| request.user.as_deref() == Some("manuscript")));
+    }
+
+    #[tokio::test]
+    async fn unreachable_endpoint_is_a_transport_error() {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let error = SqlClient::new(&format!("http://127.0.0.1:{}", port)).query("SELECT 1").await.unwrap_err();
+        assert!(error.error_name.is_none());
+        assert!(error.message.starts_with("Failed to reach SQL endpoint"), "{}", error);
+    }
+}
@@ -1,33 +1,48 @@
+use std::ops::Range;
 use ratatui::{
     buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Stylize, Color, Style, Modifier},
-    symbols::border,
+    symbols::{self, border},
     text::{Line, Text, Span},
-    widgets::{block::{Position, Title}, Block, List, ListItem, Paragraph, Widget, Tabs, Clear, Gauge, Padding},
+    widgets::{block::{Position, Title}, Block, List, ListItem, Paragraph, Widget, Tabs, Clear, Gauge, Padding, Row, Table, Wrap, Sparkline, Chart, Dataset, Axis, GraphType},
 };
-use crate::app::App;
+use crate::app::{App, ContainerStatus, SampleState};
+use crate::grid;
+use crate::finder::Finder;
+use crate::editor::TextEditor;
+use crate::logs::{LogLevel, LogView};
+use crate::jobs::{JobGroup, JobsView};
+use crate::metrics::{self, JobDashboard};
+use crate::highlight::{self, Highlight};
+use crate::completion::{Completion, CompletionKind};
+use crate::app;
+use crate::manuscript::{ManuscriptBrowser, ManuscriptDraft, PromptAction, SinkType};
+use crate::validate::{Diagnostic, Severity};
+use crate::catalog::Catalog;
 use crate::app::AppState;
 
-// Add this helper function before the draw function
-fn title_block(title: &str) -> Block<'_> {
-    Block::default()
-        .borders(ratatui::widgets::Borders::ALL)
-        .title(title)
-}
-
 // Also need to define CUSTOM_LABEL_COLOR and GAUGE2_COLOR constants
 const CUSTOM_LABEL_COLOR: Color = Color::White;
 const GAUGE2_COLOR: Style = Style::new().fg(Color::Green);
+// manuscript 查看器顶部最多显示的诊断条数
+const MAX_DIAGNOSTIC_LINES: usize = 8;
 
 pub fn draw(frame: &mut ratatui::Frame, app: &App) {
 
 
     // Create tabs
     let titles = vec!["NETWORK [1]", "MANUSCRIPTS [2]"];
-    let executing_text = String::from("Executing...");
+    let executing_text = format!("Executing... ({:.1}s)", app.sql_timer as f64 / 10.0);
+    // 链元数据来自缓存或本地文件时显示提示
+    let mut tabs_block = Block::bordered().title("Tabs");
+    if let Some(banner) = app.chains_source.banner() {
+        tabs_block = tabs_block.title_bottom(
+            Line::from(format!(" ⚠ {} ", banner)).centered().yellow().bold()
+        );
+    }
     let tabs = Tabs::new(titles)
-        .block(Block::bordered().title("Tabs"))
+        .block(tabs_block)
         .select(app.current_tab)
         .style(Style::default())
         .highlight_style(Style::default().bold());
@@ -137,14 +152,15 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
             // 如果显示表格列表，则渲染表格列表
             if app.show_tables {
                 if let Some(selected_chain) = app.chains.get(app.selected_chain_index) {
-                    let table_names: Vec<ListItem> = selected_chain.dataDictionary
-                        .keys()
+                    let table_names: Vec<ListItem> = app.catalog
+                        .tables(app.selected_chain_index)
+                        .iter()
                         .enumerate()
-                        .map(|(i, table_name)| {
+                        .map(|(i, table)| {
                             let content = if Some(i) == app.selected_table_index {
-                                Line::from(table_name.clone().bold().green())
+                                Line::from(table.name.clone().bold().green())
                             } else {
-                                Line::from(table_name.clone())
+                                Line::from(table.name.clone())
                             };
                             ListItem::new(content)
                         })
@@ -161,9 +177,18 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
             }
 
             // Add key hints at the bottom
+            let environment_hint = format!("p: Env [{}]", app.docker_manager.profile());
             let hints = vec![
                 "Enter: Select",
                 "PageUp/Down: Navigate",
+                "/: Find",
+                "f: Focus grid",
+                "m: Manuscript",
+                "c: Config",
+                "d: Container",
+                "l: Logs",
+                "j: Jobs",
+                environment_hint.as_str(),
                 "q: Quit",
             ];
             let hints_text = Text::from(hints.join(" | "));
@@ -184,13 +209,7 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
             // 右侧显示字
             if let Some(selected_chain) = app.chains.get(app.selected_chain_index) {
                 let mut data_lines = if app.show_tables && app.selected_table_index.is_some() {
-                    let table_name = selected_chain.dataDictionary
-                        .keys()
-                        .nth(app.selected_table_index.unwrap())
-                        .map(|s| s.as_str())
-                        .unwrap_or("");
-
-                    let fields = selected_chain.dataDictionary.get(table_name);
+                    let fields = app.selected_table().map(|table| &table.columns);
                     
                     let mut lines = Vec::new();
                     
@@ -206,45 +225,43 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
 
                     // Add field descriptions in table format
                     if let Some(fields) = fields {
-                        lines.extend(fields.iter().map(|item| {
-                            Line::from(vec![
+                        lines.extend(fields.iter().enumerate().map(|(i, item)| {
+                            let line = Line::from(vec![
                                 format!("{:<20}", item.name).yellow().into(),
                                 " | ".into(),
                                 format!("{:<15}", item.dataType).cyan().into(),
                                 " | ".into(),
                                 item.description.clone().white().into(),
-                            ])
+                            ]);
+                            // 命令面板跳转到的字段
+                            if Some(i) == app.highlighted_column {
+                                line.reversed()
+                            } else {
+                                line
+                            }
                         }));
                     }
 
-                    // Add example data if available
-                    if let Some(example_data) = &app.example_data {
+                    // Example data is rendered as a grid below the dictionary
+                    if app.example_data.is_none() && selected_chain.status == "Offline" {
                         lines.push(Line::from(""));
-                        lines.push(Line::from("Example Data:".bold().yellow()));
-                        
-                        // Add header
-                        lines.push(Line::from(vec![
-                            "Column Name".bold().white(),
-                            " | ".into(),
-                            "Value".bold().white(),
-                        ]));
-                        lines.push(Line::from("─".repeat(80)));  // Separator line
-
-                        // Show first row of data as example
-                        if let Some(first_row) = example_data.data.first() {
-                            for (i, value) in first_row.iter().enumerate() {
-                                if let Some(column) = example_data.columns.get(i) {
-                                    lines.push(Line::from(vec![
-                                        format!("{:<30}", column.name).yellow().into(),
-                                        " | ".into(),
-                                        value.to_string().white().into(),
-                                    ]));
-                                }
+                        lines.push(Line::from("No data available - Chain is currently offline".red().bold()));
+                    } else {
+                        match app.selected_sample() {
+                            Some(SampleState::Loading) => {
+                                lines.push(Line::from(""));
+                                lines.push(Line::from("Loading sample rows from the debug node...".yellow()));
+                            }
+                            Some(SampleState::Failed(error)) => {
+                                lines.push(Line::from(""));
+                                lines.push(Line::from(vec![
+                                    "Sample unavailable: ".red().bold(),
+                                    error.clone().red(),
+                                    " (s: Retry)".dark_gray(),
+                                ]));
                             }
+                            _ => {}
                         }
-                    } else if selected_chain.status == "Offline" {
-                        lines.push(Line::from(""));
-                        lines.push(Line::from("No data available - Chain is currently offline".red().bold()));
                     }
 
                     lines
@@ -370,55 +387,83 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
                                 .border_set(border::THICK);
                             frame.render_widget(results_block, right_chunks[1]);
 
-                            // 将下半部分分成更多份以容纳进度日志
-                            let gauge_chunks = Layout::default()
-                                .direction(Direction::Vertical)
-                                .constraints([
-                                    Constraint::Length(1),   // gauge1占用3行
-                                    Constraint::Length(1),   // docker状态占用3行
-                                    Constraint::Length(1),
-                                    Constraint::Length(2),  
-                                    Constraint::Min(0),      // 剩余空间用于显示进度日志
-                                ])
-                                .split(right_chunks[1]);
-
-                            // 只在Started状态下显示进度条
-                            if app.state == AppState::Started {
-                                // 渲染gauge1
-                                let label = Span::styled(
-                                    format!("{:.1}/100", app.progress1()),
-                                    Style::new().italic().bold().fg(CUSTOM_LABEL_COLOR),
+                            let has_query_output = app.sql_executing
+                                || app.sql_error.is_some()
+                                || app.sql_result.is_some()
+                                || !app.sql_columns.is_empty();
+                            if app.state != AppState::Started && has_query_output {
+                                // 显示查询结果
+                                let results_area = right_chunks[1].inner(ratatui::layout::Margin::new(1, 1));
+                                let results_chunks = Layout::default()
+                                    .direction(Direction::Vertical)
+                                    .constraints([Constraint::Length(3), Constraint::Min(0)])
+                                    .split(results_area);
+                                frame.render_widget(
+                                    Paragraph::new(sql_results_lines(app, &executing_text))
+                                        .wrap(ratatui::widgets::Wrap { trim: true })
+                                        .block(Block::default().padding(Padding::horizontal(1))),
+                                    results_chunks[0],
+                                );
+                                grid::render_grid(
+                                    frame,
+                                    results_chunks[1],
+                                    &format!("{} rows (f: Focus)", app.sql_data.len()),
+                                    &app.sql_columns,
+                                    &app.sql_data,
+                                    &app.results_grid,
+                                    app.grid_focused,
                                 );
-                                let gauge = Gauge::default()
-                                    .block(Block::default().padding(Padding::horizontal(1)))
-                                    .gauge_style(GAUGE2_COLOR)
-                                    .ratio(app.progress1 / 100.0)
-                                    .label(label);
-                                frame.render_widget(gauge, gauge_chunks[1]);
-                            }
-
-                            // 渲染Docker设置状态
-                            let docker_status = if app.docker_setup_in_progress {
-                                format!("Docker setup in progress... ({} seconds)", app.docker_setup_timer / 10)
                             } else {
-                                "Manuscript debug console".to_string()
-                            };
+                                // 将下半部分分成更多份以容纳进度日志
+                                let gauge_chunks = Layout::default()
+                                    .direction(Direction::Vertical)
+                                    .constraints([
+                                        Constraint::Length(1),   // gauge1占用3行
+                                        Constraint::Length(1),   // docker状态占用3行
+                                        Constraint::Length(1),
+                                        Constraint::Length(2),  
+                                        Constraint::Min(0),      // 剩余空间用于显示进度日志
+                                    ])
+                                    .split(right_chunks[1]);
+
+                                // 只在Started状态下显示进度条
+                                if app.state == AppState::Started {
+                                    // 渲染gauge1
+                                    let label = Span::styled(
+                                        app.progress_label(),
+                                        Style::new().italic().bold().fg(CUSTOM_LABEL_COLOR),
+                                    );
+                                    let gauge = Gauge::default()
+                                        .block(Block::default().padding(Padding::horizontal(1)))
+                                        .gauge_style(GAUGE2_COLOR)
+                                        .ratio(app.progress1 / 100.0)
+                                        .label(label);
+                                    frame.render_widget(gauge, gauge_chunks[1]);
+                                }
+
+                                // 渲染Docker设置状态
+                                let docker_status = if app.docker_setup_in_progress {
+                                    format!("Docker setup in progress... ({} seconds)", app.docker_setup_timer / 10)
+                                } else {
+                                    "Manuscript debug console".to_string()
+                                };
 
-                            let docker_status_widget = Paragraph::new(Text::from(
-                                Span::styled(docker_status, Style::default().fg(Color::Yellow))
-                            ))
-                            .alignment(Alignment::Center)
-                            .block(Block::default()
-                                .padding(Padding::horizontal(1)));
-                            frame.render_widget(docker_status_widget, gauge_chunks[3]);
-
-                            // 渲染进度日志
-                            let progress_lines = app.get_setup_progress_lines();
-                            let progress_widget = Paragraph::new(progress_lines)
-                                .alignment(Alignment::Left)
-                                .wrap(ratatui::widgets::Wrap { trim: true })
-                                .block(Block::default().padding(Padding::horizontal(4))); // Add padding to indent text
-                            frame.render_widget(progress_widget, gauge_chunks[4]);
+                                let docker_status_widget = Paragraph::new(Text::from(
+                                    Span::styled(docker_status, Style::default().fg(Color::Yellow))
+                                ))
+                                .alignment(Alignment::Center)
+                                .block(Block::default()
+                                    .padding(Padding::horizontal(1)));
+                                frame.render_widget(docker_status_widget, gauge_chunks[3]);
+
+                                // 渲染进度日志
+                                let progress_lines = app.get_setup_progress_lines();
+                                let progress_widget = Paragraph::new(progress_lines)
+                                    .alignment(Alignment::Left)
+                                    .wrap(ratatui::widgets::Wrap { trim: true })
+                                    .block(Block::default().padding(Padding::horizontal(4))); // Add padding to indent text
+                                frame.render_widget(progress_widget, gauge_chunks[4]);
+                            }
 
                         
                     } else {
@@ -428,21 +473,39 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
                             .title_alignment(Alignment::Center)
                             .border_set(border::THICK);
 
+                        let dictionary_scroll = app.highlighted_column
+                            .map(|column| column.saturating_sub(3) as u16)
+                            .unwrap_or(0);
                         let data_paragraph = Paragraph::new(data_lines)
                             .block(right_block)
+                            .scroll((dictionary_scroll, 0))
                             .wrap(ratatui::widgets::Wrap { trim: true });
-                        frame.render_widget(data_paragraph, chunks[1]);
+
+                        // 有示例数据时，右侧面板下方显示示例数据表格
+                        match (&app.example_data, app.show_tables) {
+                            (Some(example_data), true) => {
+                                let dictionary_chunks = Layout::default()
+                                    .direction(Direction::Vertical)
+                                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                                    .split(chunks[1]);
+                                frame.render_widget(data_paragraph, dictionary_chunks[0]);
+                                grid::render_grid(
+                                    frame,
+                                    dictionary_chunks[1],
+                                    "Example Data (f: Focus)",
+                                    &example_data.columns,
+                                    &example_data.data,
+                                    &app.example_grid,
+                                    app.grid_focused,
+                                );
+                            }
+                            _ => frame.render_widget(data_paragraph, chunks[1]),
+                        }
                     }
                 }
             }
         }
-        1 => {
-            // Tab 2 content
-            let tab2_text = Paragraph::new("tab2 text")
-                .block(Block::bordered().title("Tab 2"))
-                .alignment(Alignment::Center);
-            frame.render_widget(tab2_text, main_chunks[1]);
-        }
+        1 => draw_manuscripts(frame, &app.manuscripts, main_chunks[1]),
         _ => unreachable!(),
     }
 
@@ -482,6 +545,41 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
         ),
     );
 
+    if app.show_config {
+        draw_config(frame, app);
+    }
+
+    if app.show_container {
+        draw_container(frame, app);
+    }
+
+    if app.show_logs {
+        draw_logs(frame, &app.logs, app.docker_manager.container_name());
+    }
+
+    if app.show_jobs {
+        draw_jobs(frame, &app.jobs, &app.docker_manager.job_endpoint());
+    }
+
+    if app.show_environments {
+        draw_environments(frame, app);
+    }
+
+    if let Some(finder) = &app.finder {
+        draw_finder(frame, finder);
+    }
+
+    // 单元格详情弹窗
+    if app.grid_focused {
+        if app.results_grid_visible() {
+            if app.results_grid.show_cell {
+                grid::render_cell_popup(frame, &app.sql_columns, &app.sql_data, &app.results_grid);
+            }
+        } else if let Some(example_data) = app.example_data.as_ref().filter(|_| app.example_grid.show_cell) {
+            grid::render_cell_popup(frame, &example_data.columns, &example_data.data, &app.example_grid);
+        }
+    }
+
     if app.show_sql_window {
         // Create a floating SQL input window
         let area = frame.size();
@@ -498,8 +596,9 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
         frame.render_widget(Clear, sql_window);
 
         // Create input block
+        let run = if app.keyboard_enhanced { "Ctrl+Enter" } else { "F5/Ctrl+R" };
         let input_block = Block::bordered()
-            .title(" SQL Editor (Esc → Save & Esc) ")
+            .title(format!(" SQL Editor ({} → Run | Tab → Complete | Esc → Save & Esc) ", run))
             .title_alignment(Alignment::Center)
             .border_set(border::THICK)
             .title_style(Style::default()
@@ -507,59 +606,8 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
                 .bold()
                 .add_modifier(Modifier::UNDERLINED | Modifier::ITALIC));
 
-        // Convert the SQL input into styled text with cursor
-        let mut styled_text = Text::default();
-        let input = app.sql_input.as_str();
-        
-        // Split input into lines and process each line
-        let lines: Vec<&str> = input.split('\n').collect();
-        let mut current_pos = 0;
-        
-        for (line_idx, line) in lines.iter().enumerate() {
-            let line_length = line.len() + 1; // +1 for the newline character
-            let cursor_in_this_line = app.sql_cursor_position >= current_pos 
-                && app.sql_cursor_position < current_pos + line_length;
-            
-            if cursor_in_this_line {
-                // Calculate cursor position within this line
-                let line_cursor_pos = app.sql_cursor_position - current_pos;
-                
-                // Split the line at cursor position
-                let (before_cursor, after_cursor) = line.split_at(line_cursor_pos);
-                
-                let mut spans = Vec::new();
-                spans.push(Span::raw(before_cursor));
-                
-                // Add cursor
-                if after_cursor.chars().next().is_some() {
-                    // If there's a character at cursor position, highlight it
-                    let next_char = &after_cursor[..1];
-                    spans.push(Span::styled(
-                        next_char,
-                        Style::default().bg(Color::White).fg(Color::Black)
-                    ));
-                    spans.push(Span::raw(&after_cursor[1..]));
-                } else {
-                    // If cursor is at the end of line, show a block cursor
-                    spans.push(Span::styled(
-                        " ",
-                        Style::default().bg(Color::White)
-                    ));
-                }
-                
-                styled_text.extend(Text::from(Line::from(spans)));
-            } else {
-                // Regular line without cursor
-                styled_text.extend(Text::from(Line::from(line.to_string())));
-            }
-            
-            // Add newline if this isn't the last line
-            if line_idx < lines.len() - 1 {
-                styled_text.extend(Text::from("\n"));
-            }
-            
-            current_pos += line_length;
-        }
+        let styles = sql_styles(&app.sql_editor.text, &app.catalog, app.selected_chain_index);
+        let styled_text = editor_text(&app.sql_editor, &styles);
 
         // Render SQL input with cursor
         let sql_paragraph = Paragraph::new(styled_text)
@@ -568,6 +616,10 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
 
         frame.render_widget(sql_paragraph, sql_window);
 
+        if let Some(completion) = &app.completion {
+            draw_completion(frame, completion, &app.sql_editor, sql_window);
+        }
+
         // If there's a SQL result, show it below the input
         if let Some(result) = &app.sql_result {
             let result_text = Paragraph::new(result.as_str())
@@ -585,4 +637,955 @@ pub fn draw(frame: &mut ratatui::Frame, app: &App) {
             frame.render_widget(result_text, result_window);
         }
     }
+
+    if let Some(draft) = &app.draft {
+        draw_draft(frame, draft, &app.catalog);
+    }
+}
+
+// 带光标的编辑器文本，光标处的字符反色显示；styles 为按字节范围着色的片段，按位置排列
+fn editor_text<'a>(editor: &'a TextEditor, styles: &[(Range<usize>, Style)]) -> Text<'a> {
+    let cursor_style = Style::default().bg(Color::White).fg(Color::Black);
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    for line in editor.text.split('\n') {
+        let line_end = line_start + line.len();
+
+        // 这一行被切成的片段，未着色的部分用默认样式
+        let mut pieces: Vec<(Range<usize>, Style)> = Vec::new();
+        let mut position = line_start;
+        for (range, style) in styles.iter().filter(|(range, _)| range.start < line_end && range.end > line_start) {
+            let range = range.start.max(position)..range.end.min(line_end);
+            if range.start > position {
+                pieces.push((position..range.start, Style::default()));
+            }
+            if !range.is_empty() {
+                position = range.end;
+                pieces.push((range, *style));
+            }
+        }
+        if position < line_end {
+            pieces.push((position..line_end, Style::default()));
+        }
+
+        let mut spans = Vec::new();
+        for (range, style) in pieces {
+            if !range.contains(&editor.cursor) {
+                spans.push(Span::styled(&editor.text[range], style));
+                continue;
+            }
+            let c = editor.text[editor.cursor..].chars().next().map(char::len_utf8).unwrap_or(1);
+            spans.push(Span::styled(&editor.text[range.start..editor.cursor], style));
+            spans.push(Span::styled(&editor.text[editor.cursor..editor.cursor + c], cursor_style));
+            spans.push(Span::styled(&editor.text[editor.cursor + c..range.end], style));
+        }
+        // 行尾显示块状光标
+        if editor.cursor == line_end {
+            spans.push(Span::styled(" ", Style::default().bg(Color::White)));
+        }
+        lines.push(Line::from(spans));
+        line_start = line_end + 1;
+    }
+    Text::from(lines)
+}
+
+// 补全弹窗显示在光标下一行，下方放不下时显示在上方
+fn draw_completion(frame: &mut ratatui::Frame, completion: &Completion, editor: &TextEditor, editor_area: Rect) {
+    let area = frame.area();
+    let before = &editor.text[..editor.cursor];
+    let line = before.matches('\n').count() as u16;
+    let column = before[before.rfind('\n').map(|pos| pos + 1).unwrap_or(0)..].chars().count() as u16;
+    let prefix = editor.text[completion.range.clone()].chars().count() as u16;
+
+    let label_width = completion.candidates.iter().map(|candidate| candidate.text.chars().count()).max().unwrap_or(0);
+    let type_width = completion.candidates.iter().map(|candidate| candidate.data_type.chars().count()).max().unwrap_or(0);
+    let description_width = completion.candidates.iter()
+        .map(|candidate| candidate.description.chars().count() + candidate.table.as_ref().map(|table| table.chars().count() + 1).unwrap_or(0))
+        .max()
+        .unwrap_or(0);
+    let width = ((label_width + type_width + description_width + 6) as u16).clamp(36, (area.width * 2 / 3).max(36)).min(area.width);
+    let height = (completion.candidates.len() as u16 + 2).min(12);
+
+    let x = (editor_area.x + 1 + column.saturating_sub(prefix)).min(area.width.saturating_sub(width));
+    let below = editor_area.y + 2 + line;
+    let y = if below + height <= area.height { below } else { (below - 1).saturating_sub(height) };
+    let popup = Rect::new(x, y, width, height).intersection(area);
+
+    let title = match completion.kind {
+        CompletionKind::Table => " Tables ",
+        CompletionKind::Column => " Columns ",
+    };
+    let block = Block::bordered()
+        .title(title)
+        .title_bottom(Line::from(format!(" {}/{} | Tab: Insert | Esc: Close ", completion.selected + 1, completion.candidates.len())).right_aligned())
+        .border_set(border::THICK)
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let visible = inner.height as usize;
+    let skip = (completion.selected + 1).saturating_sub(visible);
+    let items: Vec<ListItem> = completion.candidates.iter()
+        .enumerate()
+        .skip(skip)
+        .take(visible)
+        .map(|(i, candidate)| {
+            let mut spans = vec![
+                format!("{:<width$}  ", candidate.text, width = label_width).white().bold(),
+                format!("{:<width$}  ", candidate.data_type, width = type_width).cyan(),
+            ];
+            if let Some(table) = &candidate.table {
+                spans.push(format!("{} ", table).yellow());
+            }
+            spans.push(candidate.description.clone().dark_gray());
+            if i == completion.selected {
+                ListItem::new(Line::from(spans)).style(Style::default().bg(Color::DarkGray))
+            } else {
+                ListItem::new(Line::from(spans))
+            }
+        })
+        .collect();
+    frame.render_widget(List::new(items), inner);
+}
+
+// SQL 编辑器的语法高亮，数据字典中找不到的名字加下划线
+fn sql_styles(sql: &str, catalog: &Catalog, chain_index: usize) -> Vec<(Range<usize>, Style)> {
+    highlight::highlight(sql, catalog, chain_index)
+        .into_iter()
+        .map(|(range, highlight)| {
+            let style = match highlight {
+                Highlight::Keyword => Style::default().fg(Color::Magenta).bold(),
+                Highlight::String => Style::default().fg(Color::Green),
+                Highlight::Number => Style::default().fg(Color::LightCyan),
+                Highlight::Comment => Style::default().fg(Color::DarkGray).italic(),
+                Highlight::Operator => Style::default().fg(Color::Yellow),
+                Highlight::Punctuation => Style::default().fg(Color::Gray),
+                Highlight::Table => Style::default().fg(Color::Cyan).bold(),
+                Highlight::Column => Style::default().fg(Color::LightBlue),
+                Highlight::Function => Style::default().fg(Color::LightYellow),
+                Highlight::Alias => Style::default().fg(Color::White),
+                Highlight::Unknown => Style::default().fg(Color::LightRed).add_modifier(Modifier::UNDERLINED),
+            };
+            (range, style)
+        })
+        .collect()
+}
+
+// 渲染 SQL 执行状态、错误和返回的数据
+fn sql_results_lines<'a>(app: &'a App, executing_text: &'a str) -> Text<'a> {
+    let mut lines = Vec::new();
+
+    if app.sql_executing {
+        lines.push(Line::from(executing_text.yellow()));
+    } else if let Some(result) = &app.sql_result {
+        lines.push(Line::from(result.as_str().green()));
+    }
+    if let Some(stats) = &app.sql_stats {
+        lines.push(Line::from(stats.summary().cyan()));
+    }
+    if let Some(error) = &app.sql_error {
+        lines.push(Line::from(error.to_string().red().bold()));
+    }
+
+    Text::from(lines)
+}
+
+// MANUSCRIPTS 页：左侧为工作区中的文件列表，右侧为选中文件的内容和校验结果
+fn draw_manuscripts(frame: &mut ratatui::Frame, browser: &ManuscriptBrowser, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(area);
+    let left_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(4)])
+        .split(chunks[0]);
+
+    let list_block = Block::bordered()
+        .border_set(border::THICK)
+        .title(format!(" Manuscripts ({}) ", browser.dir.display()))
+        .title_alignment(Alignment::Center);
+    if browser.files.is_empty() {
+        let text = match &browser.error {
+            Some(error) => Text::from(error.clone().red()),
+            None => Text::from(vec![
+                Line::from(format!("No manuscripts in {}", browser.dir.display()).dark_gray()),
+                Line::from("Press n to create one.".dark_gray()),
+            ]),
+        };
+        frame.render_widget(Paragraph::new(text).block(list_block).wrap(Wrap { trim: false }), left_chunks[0]);
+    } else {
+        let visible = left_chunks[0].height.saturating_sub(3) as usize;
+        let skip = (browser.selected + 1).saturating_sub(visible);
+        let rows = browser.files.iter().enumerate().skip(skip).map(|(i, file)| {
+            let status = if file.errors() > 0 {
+                "✗".red()
+            } else if !file.diagnostics.is_empty() {
+                "!".yellow()
+            } else {
+                "✓".green()
+            };
+            let row = Row::new(vec![
+                Line::from(status),
+                Line::from(file.name().yellow()),
+                Line::from(file.chain().unwrap_or("-").to_string()),
+                Line::from(file.source_tables().join(", ")),
+                Line::from(file.sink().unwrap_or_else(|| "-".to_string()).dark_gray()),
+            ]);
+            if i == browser.selected {
+                row.style(Style::default().bg(Color::DarkGray))
+            } else {
+                row
+            }
+        });
+        let table = Table::new(rows, [
+            Constraint::Length(2),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+            Constraint::Fill(2),
+            Constraint::Fill(2),
+        ])
+            .header(Row::new(vec!["", "Name", "Chain", "Sources", "Sink"]).bold())
+            .block(list_block);
+        frame.render_widget(table, left_chunks[0]);
+    }
+
+    let status = if let Some(file) = browser.selected_file().filter(|_| browser.confirm_delete) {
+        Line::from(format!("Delete {}? (y/n)", file.path.display()).yellow().bold())
+    } else if let Some(message) = &browser.message {
+        Line::from(message.clone().cyan())
+    } else {
+        Line::default()
+    };
+    let hints = if browser.viewing {
+        "↑↓/PgUp/PgDn: Scroll | Esc: Back to list"
+    } else {
+        "Enter: Open | s: Submit | n: New | D: Duplicate | x: Delete | g: Rescan | q: Quit"
+    };
+    let hints_block = Block::bordered()
+        .title(" Controls ")
+        .title_alignment(Alignment::Center)
+        .border_set(border::THICK);
+    frame.render_widget(
+        Paragraph::new(vec![Line::from(hints), status]).block(hints_block).alignment(Alignment::Center),
+        left_chunks[1],
+    );
+
+    let mut viewer_block = Block::bordered()
+        .border_set(border::THICK)
+        .padding(Padding::horizontal(1));
+    if browser.viewing {
+        viewer_block = viewer_block.border_style(Style::default().fg(Color::Yellow));
+    }
+    let Some(file) = browser.selected_file() else {
+        frame.render_widget(viewer_block, chunks[1]);
+        draw_name_prompt(frame, browser);
+        return;
+    };
+    viewer_block = viewer_block
+        .title(format!(" {} ", file.path.display()))
+        .title_alignment(Alignment::Center);
+    let inner = viewer_block.inner(chunks[1]);
+    frame.render_widget(viewer_block, chunks[1]);
+
+    let mut header = diagnostic_lines(&file.diagnostics, MAX_DIAGNOSTIC_LINES);
+    if let Ok(manuscript) = &file.manuscript {
+        header.push(Line::from(format!(
+            "specVersion {} · parallelism {}",
+            manuscript.spec_version.as_deref().unwrap_or("-"),
+            manuscript.parallelism.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()),
+        ).dark_gray()));
+    }
+    header.push(Line::from("─".repeat(inner.width as usize).dark_gray()));
+
+    let viewer_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(header.len() as u16), Constraint::Min(0)])
+        .split(inner);
+    frame.render_widget(Paragraph::new(header).wrap(Wrap { trim: false }), viewer_chunks[0]);
+
+    browser.height.set(viewer_chunks[1].height as usize);
+    let content: Vec<Line> = file.content
+        .lines()
+        .enumerate()
+        .skip(browser.scroll)
+        .take(viewer_chunks[1].height as usize)
+        .map(|(i, line)| {
+            // 有诊断的行，行号用对应的颜色标出
+            let number = format!("{:>4} ", i + 1);
+            let number = match file.diagnostics.iter().filter(|d| d.line == i + 1).map(|d| d.severity).min() {
+                Some(Severity::Error) => number.red().bold(),
+                Some(Severity::Warning) => number.yellow().bold(),
+                None => number.dark_gray(),
+            };
+            Line::from(vec![number, line.to_string().white()])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(content), viewer_chunks[1]);
+
+    draw_name_prompt(frame, browser);
+}
+
+// 生成 manuscript：先选择名称和 sink，再检查和修改生成的 YAML
+fn draw_draft(frame: &mut ratatui::Frame, draft: &ManuscriptDraft, catalog: &Catalog) {
+    let area = frame.area();
+    let Some(editor) = &draft.editor else {
+        let width = (area.width as f32 * 0.6) as u16;
+        let height = 11.min(area.height);
+        let popup = Rect::new((area.width - width) / 2, (area.height - height) / 3, width, height);
+        let block = Block::bordered()
+            .title(" Generate manuscript ")
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::from(" Tab/←→: Sink | Enter: Generate | Esc: Cancel ").centered())
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(Color::Yellow))
+            .padding(Padding::uniform(1));
+
+        let field = |name: &'static str| format!("{:<8}", name).yellow().bold();
+        let mut sinks = vec![field("Sink")];
+        for sink in SinkType::ALL {
+            let label = format!(" {} ", sink.as_str());
+            sinks.push(if sink == draft.sink { label.black().on_white().bold() } else { label.white() });
+            sinks.push(" ".into());
+        }
+        let sql_lines = draft.sql.trim().lines().count();
+        let mut lines = vec![
+            Line::from(vec![field("Source"), format!("{}.{}", draft.chain, draft.table).white()]),
+            Line::from(vec![
+                field("SQL"),
+                draft.sql.trim().lines().next().unwrap_or_default().to_string().white(),
+                if sql_lines > 1 { format!(" (+{} lines)", sql_lines - 1).dark_gray() } else { "".into() },
+            ]),
+            Line::from(vec![
+                field("Name"),
+                draft.name.as_str().white(),
+                Span::styled(" ", Style::default().bg(Color::White)),
+            ]),
+            Line::from(sinks),
+        ];
+        if let Some(message) = &draft.message {
+            lines.push(Line::default());
+            lines.push(Line::from(message.clone().red()));
+        }
+        frame.render_widget(Clear, popup);
+        frame.render_widget(Paragraph::new(lines).block(block), popup);
+        return;
+    };
+
+    let width = (area.width as f32 * 0.8) as u16;
+    let height = (area.height as f32 * 0.85) as u16;
+    let popup = Rect::new((area.width - width) / 2, (area.height - height) / 2, width, height);
+    let block = Block::bordered()
+        .title(format!(" {}/manuscript.yaml ", draft.name))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(" Ctrl+S: Save to workspace | Esc: Back ").centered())
+        .border_set(border::THICK)
+        .border_style(Style::default().fg(Color::Yellow))
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let mut status = Vec::new();
+    if let Some(message) = &draft.message {
+        status.push(Line::from(message.clone().red().bold()));
+    }
+    status.extend(diagnostic_lines(&draft.diagnostics(catalog), 5));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1), Constraint::Length(status.len().min(8) as u16)])
+        .split(inner);
+
+    // 保持光标所在的行可见
+    let offset = editor.cursor_line().saturating_sub(chunks[0].height.saturating_sub(1) as usize);
+    frame.render_widget(
+        Paragraph::new(editor_text(editor, &[])).scroll((offset as u16, 0)).style(Style::default().fg(Color::White)),
+        chunks[0],
+    );
+    frame.render_widget(Paragraph::new("─".repeat(chunks[1].width as usize).dark_gray()), chunks[1]);
+    frame.render_widget(Paragraph::new(status).wrap(Wrap { trim: false }), chunks[2]);
+}
+
+// 校验结果摘要和前 limit 条诊断
+fn diagnostic_lines(diagnostics: &[Diagnostic], limit: usize) -> Vec<Line<'static>> {
+    let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+    let warnings = diagnostics.len() - errors;
+    let mut lines = vec![match (errors, warnings) {
+        (0, 0) => Line::from("✓ Valid".green().bold()),
+        (0, _) => Line::from(format!("! {} warning(s)", warnings).yellow().bold()),
+        _ => Line::from(format!("✗ {} error(s), {} warning(s)", errors, warnings).red().bold()),
+    }];
+    lines.extend(diagnostics.iter().take(limit).map(|diagnostic| {
+        let style = match diagnostic.severity {
+            Severity::Error => Style::default().fg(Color::Red),
+            Severity::Warning => Style::default().fg(Color::Yellow),
+        };
+        Line::from(vec![
+            format!("  {}:{} ", diagnostic.line, diagnostic.column).dark_gray(),
+            Span::styled(diagnostic.message.clone(), style),
+        ])
+    }));
+    if diagnostics.len() > limit {
+        lines.push(Line::from(format!("  ... {} more", diagnostics.len() - limit).dark_gray()));
+    }
+    lines
+}
+
+// 新建或复制 manuscript 时输入名称
+fn draw_name_prompt(frame: &mut ratatui::Frame, browser: &ManuscriptBrowser) {
+    let Some(prompt) = &browser.prompt else {
+        return;
+    };
+    let area = frame.area();
+    let width = (area.width as f32 * 0.5) as u16;
+    let height = 5.min(area.height);
+    let popup = Rect::new((area.width - width) / 2, (area.height - height) / 3, width, height);
+
+    let title = match prompt.action {
+        PromptAction::Create => " New manuscript ",
+        PromptAction::Duplicate => " Duplicate manuscript ",
+    };
+    let block = Block::bordered()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(" Enter: Create | Esc: Cancel ").centered())
+        .border_set(border::THICK)
+        .border_style(Style::default().fg(Color::Yellow))
+        .padding(Padding::horizontal(1));
+    let input = Line::from(vec![
+        "Name: ".yellow().bold(),
+        prompt.input.as_str().white(),
+        Span::styled(" ", Style::default().bg(Color::White)),
+    ]);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(vec![Line::default(), input]).block(block), popup);
+}
+
+// 显示当前生效的配置及每个值的来源
+fn draw_config(frame: &mut ratatui::Frame, app: &App) {
+    let area = frame.area();
+    let width = (area.width as f32 * 0.8) as u16;
+    let entries = app.config.entries();
+    let height = (entries.len() as u16 + 5).min(area.height);
+    let popup = Rect::new((area.width - width) / 2, (area.height - height) / 2, width, height);
+
+    let config_file = app.config.path
+        .as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "no config file".to_string());
+    let block = Block::bordered()
+        .title(format!(" Configuration ({}) ", config_file))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(" c/Esc: Close ").centered())
+        .border_set(border::THICK)
+        .padding(Padding::uniform(1));
+
+    let rows = entries.into_iter().map(|(name, value, source)| {
+        Row::new(vec![
+            Line::from(name.yellow()),
+            Line::from(value.white()),
+            Line::from(source.dark_gray()),
+        ])
+    });
+    let table = Table::new(rows, [Constraint::Length(16), Constraint::Fill(3), Constraint::Fill(1)])
+        .header(Row::new(vec!["Setting", "Value", "Source"]).bold())
+        .block(block);
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(table, popup);
+}
+
+// 调试容器的状态和生命周期操作
+fn draw_container(frame: &mut ratatui::Frame, app: &App) {
+    let area = frame.area();
+    let width = (area.width as f32 * 0.6) as u16;
+    let height = 14.min(area.height);
+    let popup = Rect::new((area.width - width) / 2, (area.height - height) / 2, width, height);
+
+    let block = Block::bordered()
+        .title(format!(" Container {} ", app.docker_manager.container_name()))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(" t: Stop | r: Restart | x: Remove | n: Recreate | d/Esc: Close ").centered())
+        .border_set(border::THICK)
+        .padding(Padding::horizontal(1));
+
+    let field = |name: &'static str, value: Span<'static>| Line::from(vec![format!("{:<10}", name).yellow(), value]);
+    let mut lines = Vec::new();
+    match &app.container {
+        ContainerStatus::Unknown => lines.push(Line::from("Checking container...".dark_gray())),
+        ContainerStatus::Missing => {
+            lines.push(Line::from("Container does not exist.".dark_gray()));
+            lines.push(Line::from("Press r on the main screen to set it up, or n to create it.".dark_gray()));
+        }
+        ContainerStatus::Failed(e) => lines.push(Line::from(e.clone().red())),
+        ContainerStatus::Found(info) => {
+            let state = if info.running {
+                info.status.clone().green().bold()
+            } else {
+                format!("{} (exit code {})", info.status, info.exit_code).red().bold()
+            };
+            lines.push(field("State", state));
+            match (info.uptime(), info.finished_at) {
+                (Some(uptime), _) => lines.push(field("Uptime", uptime.white())),
+                (None, Some(finished_at)) => lines.push(field(
+                    "Stopped",
+                    finished_at.format("%Y-%m-%d %H:%M:%S UTC").to_string().white(),
+                )),
+                (None, None) => {}
+            }
+            lines.push(field("Image", info.image.clone().white()));
+            lines.push(field("ID", info.id.chars().take(12).collect::<String>().dark_gray()));
+            if info.ports.is_empty() {
+                lines.push(field("Ports", "-".dark_gray()));
+            }
+            for (i, (container_port, host)) in info.ports.iter().enumerate() {
+                let name = if i == 0 { "Ports" } else { "" };
+                lines.push(field(name, format!("{} → {}", host, container_port).white()));
+            }
+        }
+    }
+
+    lines.push(Line::default());
+    if let Some(action) = app.container_confirm {
+        lines.push(Line::from(format!("Really {} the container? (y/n)", action).yellow().bold()));
+    } else if let Some(action) = app.container_busy {
+        lines.push(Line::from(format!("Running {}...", action).yellow()));
+    } else if app.container_died {
+        lines.push(Line::from("The container stopped since setup. Press r to start it again.".red().bold()));
+    } else if let Some(message) = &app.container_message {
+        lines.push(Line::from(message.clone().cyan()));
+    }
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: false }), popup);
+}
+
+// 调试环境列表，Enter 切换当前环境
+fn draw_environments(frame: &mut ratatui::Frame, app: &App) {
+    let area = frame.area();
+    let width = (area.width as f32 * 0.8) as u16;
+    let height = (app.environments.len() as u16 + 5).min(area.height);
+    let popup = Rect::new((area.width - width) / 2, (area.height - height) / 2, width, height);
+
+    let block = Block::bordered()
+        .title(" Debug environments ")
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(" ↑↓: Move | Enter: Switch | p/Esc: Close ").centered())
+        .border_set(border::THICK)
+        .padding(Padding::horizontal(1));
+
+    let rows = app.environments.iter().enumerate().map(|(i, environment)| {
+        let marker = if i == app.active_environment { "●".green() } else { " ".into() };
+        let row = Row::new(vec![
+            Line::from(marker),
+            Line::from(environment.profile().to_string().yellow()),
+            Line::from(environment.container_name().to_string()),
+            Line::from(environment.ports().to_string()),
+            Line::from(environment.image().to_string().dark_gray()),
+        ]);
+        if i == app.environment_selected {
+            row.style(Style::default().bg(Color::DarkGray))
+        } else {
+            row
+        }
+    });
+    let table = Table::new(rows, [
+        Constraint::Length(2),
+        Constraint::Fill(1),
+        Constraint::Fill(2),
+        Constraint::Length(22),
+        Constraint::Fill(3),
+    ])
+        .header(Row::new(vec!["", "Profile", "Container", "Ports", "Image"]).bold())
+        .block(block);
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(table, popup);
+}
+
+// 容器日志面板
+fn draw_logs(frame: &mut ratatui::Frame, logs: &LogView, container_name: &str) {
+    let area = frame.area();
+    let width = (area.width as f32 * 0.9) as u16;
+    let height = (area.height as f32 * 0.85) as u16;
+    let popup = Rect::new((area.width - width) / 2, (area.height - height) / 2, width, height);
+
+    let state = if !logs.following {
+        " disconnected ".red()
+    } else if logs.paused {
+        format!(" paused, {} new lines ", logs.unseen).yellow()
+    } else {
+        " following ".green()
+    };
+    let block = Block::bordered()
+        .title(Line::from(vec![format!(" Logs {} ", container_name).bold(), state]))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(
+            " p: Pause | ↑↓/PgUp/PgDn: Scroll | /: Search | n/N: Older/Newer match | w: Save | l/Esc: Close "
+        ).centered())
+        .border_set(border::THICK);
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+    logs.height.set(chunks[0].height as usize);
+
+    let lines: Vec<Line> = logs.lines
+        .range(logs.visible())
+        .map(|line| {
+            let style = match LogLevel::detect(line) {
+                LogLevel::Error => Style::default().fg(Color::Red),
+                LogLevel::Warn => Style::default().fg(Color::Yellow),
+                LogLevel::Info => Style::default().fg(Color::White),
+                LogLevel::Debug => Style::default().fg(Color::DarkGray),
+                LogLevel::Other => Style::default().fg(Color::Gray),
+            };
+            // 高亮搜索命中的部分
+            let mut spans = Vec::new();
+            let mut last = 0;
+            for range in logs.match_ranges(line) {
+                spans.push(Span::styled(&line[last..range.start], style));
+                spans.push(Span::styled(&line[range.clone()], Style::default().bg(Color::Yellow).fg(Color::Black)));
+                last = range.end;
+            }
+            spans.push(Span::styled(&line[last..], style));
+            Line::from(spans)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+    let status = if logs.searching {
+        Line::from(vec![
+            "/ ".yellow().bold(),
+            logs.search.as_str().white(),
+            Span::styled(" ", Style::default().bg(Color::White)),
+        ])
+    } else if let Some(message) = &logs.message {
+        Line::from(message.as_str().cyan())
+    } else if logs.lines.is_empty() {
+        Line::from("Waiting for output...".dark_gray())
+    } else {
+        Line::from(format!("{} lines", logs.lines.len()).dark_gray())
+    };
+    frame.render_widget(Paragraph::new(status), chunks[1]);
+}
+
+// job manager 上的作业列表，Enter 查看异常
+fn draw_jobs(frame: &mut ratatui::Frame, jobs: &JobsView, endpoint: &str) {
+    let area = frame.area();
+    let width = (area.width as f32 * 0.9) as u16;
+    let height = (area.height as f32 * 0.8) as u16;
+    let popup = Rect::new((area.width - width) / 2, (area.height - height) / 2, width, height);
+
+    let hints = if jobs.dashboard.is_some() {
+        " m/Esc: Back "
+    } else if jobs.exceptions.is_some() {
+        " ↑↓/PgUp/PgDn: Scroll | Enter/Esc: Back "
+    } else {
+        " ↑↓: Move | Enter: Exceptions | m: Metrics | c: Cancel | g: Refresh | j/Esc: Close "
+    };
+    let counts = format!(
+        " {} running · {} failed · {} finished ",
+        jobs.count(JobGroup::Running), jobs.count(JobGroup::Failed), jobs.count(JobGroup::Finished)
+    );
+    let block = Block::bordered()
+        .title(Line::from(vec![format!(" Jobs {} ", endpoint).bold(), counts.dark_gray()]))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(hints).centered())
+        .border_set(border::THICK)
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    if let Some(dashboard) = &jobs.dashboard {
+        draw_job_dashboard(frame, dashboard, chunks[0]);
+    } else if let Some((id, result)) = &jobs.exceptions {
+        let lines: Vec<Line> = match result {
+            Ok(exceptions) if exceptions.is_empty() => vec![Line::from("No exceptions reported.".dark_gray())],
+            Ok(exceptions) => exceptions.lines()
+                .into_iter()
+                .map(|line| {
+                    // 堆栈行以 tab 开头，终端里按空格显示
+                    let indented = line.starts_with(char::is_whitespace);
+                    let line = line.replace('\t', "    ");
+                    if indented {
+                        Line::from(line.dark_gray())
+                    } else {
+                        Line::from(line.red())
+                    }
+                })
+                .collect(),
+            Err(e) => vec![Line::from(e.clone().yellow())],
+        };
+        jobs.height.set(chunks[0].height.saturating_sub(2) as usize);
+        let block = Block::bordered()
+            .title(format!(" Exceptions of {} ", id))
+            .border_style(Style::default().fg(Color::Yellow));
+        frame.render_widget(Paragraph::new(lines).block(block).scroll((jobs.scroll as u16, 0)), chunks[0]);
+    } else if jobs.jobs.is_empty() {
+        let text = match &jobs.error {
+            Some(e) => Line::from(e.clone().red()),
+            None if !jobs.loaded => Line::from("Loading jobs...".dark_gray()),
+            None => Line::from("No jobs yet. Submit a manuscript from the MANUSCRIPTS tab (s).".dark_gray()),
+        };
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), chunks[0]);
+    } else {
+        let rows = jobs.jobs.iter().enumerate().map(|(i, job)| {
+            let state = match job.group() {
+                JobGroup::Running => job.state.clone().green(),
+                JobGroup::Failed => job.state.clone().red().bold(),
+                JobGroup::Finished => job.state.clone().dark_gray(),
+            };
+            let row = Row::new(vec![
+                Line::from(state),
+                Line::from(job.name.clone().yellow()),
+                Line::from(job.started()),
+                Line::from(job.elapsed()),
+                Line::from(job.id.clone().dark_gray()),
+            ]);
+            if i == jobs.selected {
+                row.style(Style::default().bg(Color::DarkGray))
+            } else {
+                row
+            }
+        });
+        let table = Table::new(rows, [
+            Constraint::Length(12),
+            Constraint::Fill(2),
+            Constraint::Length(19),
+            Constraint::Length(11),
+            Constraint::Length(32),
+        ])
+            .header(Row::new(vec!["State", "Name", "Started", "Duration", "ID"]).bold());
+        frame.render_widget(table, chunks[0]);
+    }
+
+    let status = if let Some(e) = jobs.dashboard.as_ref().and_then(|dashboard| dashboard.error.as_ref()) {
+        Line::from(e.clone().red())
+    } else if let Some(dashboard) = &jobs.dashboard {
+        Line::from(format!("{} samples, polling every 2s", dashboard.history.len()).dark_gray())
+    } else if let Some(job) = jobs.selected_job().filter(|_| jobs.confirm_cancel) {
+        Line::from(format!("Cancel job {} ({})? (y/n)", job.name, job.id).yellow().bold())
+    } else if let Some(message) = &jobs.message {
+        Line::from(message.clone().cyan())
+    } else if let Some(e) = jobs.error.as_ref().filter(|_| !jobs.jobs.is_empty()) {
+        // 刷新失败时保留上一次的列表
+        Line::from(e.clone().red())
+    } else {
+        Line::default()
+    };
+    frame.render_widget(Paragraph::new(status), chunks[1]);
+}
+
+// 单个作业的吞吐、反压和 checkpoint 指标
+fn draw_job_dashboard(frame: &mut ratatui::Frame, dashboard: &JobDashboard, area: Rect) {
+    let Some(current) = &dashboard.metrics else {
+        let text = if dashboard.error.is_some() { "" } else { "Loading metrics..." };
+        frame.render_widget(Paragraph::new(Line::from(text.dark_gray())), area);
+        return;
+    };
+    let history = &dashboard.history;
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(4),
+            Constraint::Length(6),
+            Constraint::Min(8),
+            Constraint::Length(current.vertices.len() as u16 + 1),
+        ])
+        .split(area);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            dashboard.job_name.clone().yellow().bold(),
+            format!("  {}", dashboard.job_id).dark_gray(),
+        ])),
+        rows[0],
+    );
+
+    // 反压和 checkpoint 成功率
+    let gauges = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Fill(1), Constraint::Fill(1)])
+        .split(rows[1]);
+    let backpressure = current.backpressure();
+    app::render_gauge(
+        "Backpressure",
+        backpressure,
+        format!("{:.0}%", backpressure * 100.0),
+        backpressure >= 0.5,
+        gauges[0],
+        frame.buffer_mut(),
+    );
+    let counts = &current.checkpoints.counts;
+    let finished = counts.completed + counts.failed;
+    app::render_gauge(
+        "Checkpoints",
+        if finished == 0 { 0.0 } else { counts.completed as f64 / finished as f64 },
+        format!("{} completed, {} failed, {} in progress", counts.completed, counts.failed, counts.in_progress),
+        counts.failed > 0,
+        gauges[1],
+        frame.buffer_mut(),
+    );
+
+    // 吞吐和反压的历史
+    let sparklines = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Fill(2), Constraint::Fill(2), Constraint::Fill(1)])
+        .split(rows[2]);
+    let series = [
+        (
+            format!(" Records in/s {} ", metrics::format_rate(current.records_in())),
+            history.series(|sample| sample.records_in),
+            Color::Cyan,
+            None,
+        ),
+        (
+            format!(" Records out/s {} ", metrics::format_rate(current.records_out())),
+            history.series(|sample| sample.records_out),
+            Color::Green,
+            None,
+        ),
+        (
+            " Backpressure % ".to_string(),
+            history.series(|sample| sample.backpressure * 100.0),
+            Color::Red,
+            Some(100),
+        ),
+    ];
+    for ((title, data, color, max), area) in series.into_iter().zip(sparklines.iter()) {
+        let block = Block::bordered().title(title);
+        // 只显示能放下的最新采样
+        let width = block.inner(*area).width as usize;
+        let data = &data[data.len().saturating_sub(width)..];
+        let mut sparkline = Sparkline::default().block(block).data(data).style(Style::default().fg(color));
+        if let Some(max) = max {
+            sparkline = sparkline.max(max);
+        }
+        frame.render_widget(sparkline, *area);
+    }
+
+    // checkpoint 耗时，失败的时刻用红点标出
+    let durations = history.checkpoint_durations();
+    let failures = history.checkpoint_failures();
+    let max_duration = durations.iter().chain(&failures).map(|(_, ms)| *ms).fold(0.0, f64::max).max(1.0);
+    let [start, end] = history.time_bounds();
+    let latest = match &current.checkpoints.latest.completed {
+        Some(checkpoint) => format!(" Checkpoint duration (#{} took {} ms) ", checkpoint.id, checkpoint.end_to_end_duration),
+        None => " Checkpoint duration (none completed) ".to_string(),
+    };
+    let mut block = Block::bordered().title(latest);
+    if let Some(failed) = &current.checkpoints.latest.failed {
+        let message = failed.failure_message.as_deref().unwrap_or("unknown reason");
+        block = block.title_bottom(Line::from(format!(" #{} failed: {} ", failed.id, message).red()));
+    }
+    let chart = Chart::new(vec![
+        Dataset::default()
+            .name("duration")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&durations),
+        Dataset::default()
+            .name("failed")
+            .marker(symbols::Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Red))
+            .data(&failures),
+    ])
+        .block(block)
+        .x_axis(Axis::default()
+            .bounds([start, end])
+            .labels([format!("-{:.0}s", end - start), "now".to_string()])
+            .style(Style::default().fg(Color::DarkGray)))
+        .y_axis(Axis::default()
+            .bounds([0.0, max_duration * 1.2])
+            .labels(["0".to_string(), format!("{:.0} ms", max_duration)])
+            .style(Style::default().fg(Color::DarkGray)));
+    frame.render_widget(chart, rows[3]);
+
+    // 每个算子
+    let vertex_rows = current.vertices.iter().map(|vertex| {
+        let backpressure = format!("{:.0}%", vertex.backpressure * 100.0);
+        Row::new(vec![
+            Line::from(vertex.name.clone()),
+            Line::from(vertex.parallelism.to_string()),
+            Line::from(metrics::format_rate(vertex.records_in)),
+            Line::from(metrics::format_rate(vertex.records_out)),
+            Line::from(if vertex.backpressure >= 0.5 { backpressure.red() } else { backpressure.green() }),
+        ])
+    });
+    let table = Table::new(vertex_rows, [
+        Constraint::Fill(1),
+        Constraint::Length(11),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(12),
+    ])
+        .header(Row::new(vec!["Operator", "Parallelism", "In/s", "Out/s", "Backpressure"]).bold());
+    frame.render_widget(table, rows[4]);
+}
+
+// `/` 命令面板
+fn draw_finder(frame: &mut ratatui::Frame, finder: &Finder) {
+    let area = frame.area();
+    let width = (area.width as f32 * 0.6) as u16;
+    let height = (area.height as f32 * 0.6) as u16;
+    let popup = Rect::new((area.width - width) / 2, (area.height - height) / 4, width, height);
+
+    let block = Block::bordered()
+        .title(" Find chain / table / column ")
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(" ↑↓: Move | Enter: Jump | Esc: Close ").centered())
+        .border_set(border::THICK)
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)])
+        .split(inner);
+
+    let input = Line::from(vec![
+        "/ ".yellow().bold(),
+        finder.query.as_str().white(),
+        Span::styled(" ", Style::default().bg(Color::White)),
+    ]);
+    frame.render_widget(Paragraph::new(input), chunks[0]);
+
+    let visible = chunks[1].height as usize;
+    let skip = (finder.selected + 1).saturating_sub(visible);
+    let items: Vec<ListItem> = finder.results()
+        .enumerate()
+        .skip(skip)
+        .take(visible)
+        .map(|(i, item)| {
+            let line = Line::from(vec![
+                format!("{:<7} ", item.kind()).dark_gray(),
+                item.label.clone().white().bold(),
+                format!("  {}", item.detail).dark_gray(),
+            ]);
+            if i == finder.selected {
+                ListItem::new(line).style(Style::default().bg(Color::DarkGray))
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+    frame.render_widget(List::new(items), chunks[1]);
 }
@@ -0,0 +1,655 @@
+use std::{collections::{HashMap, HashSet}, fmt, ops::Range, path::{Path, PathBuf}};
+use crate::app::DataDictionaryItem;
+use crate::catalog::{Catalog, CatalogTable};
+use crate::lexer::{self, Token, TokenKind};
+use crate::manuscript::Manuscript;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: usize,    // 从 1 开始
+    pub column: usize,  // 从 1 开始，按字符计
+    pub severity: Severity,
+    pub message: String,
+}
+
+// 与编译器相同的格式，方便编辑器和脚本解析
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: {}: {}", self.path.display(), self.line, self.column, self.severity, self.message)
+    }
+}
+
+// 校验 manuscript：YAML 结构、引用的名字，以及链、表、字段和字段类型。
+// catalog 为空（链元数据不可用）时跳过数据字典相关的检查
+pub fn validate(path: &Path, content: &str, catalog: &Catalog) -> Vec<Diagnostic> {
+    let locator = Locator::new(content);
+    let mut report = Report { path, diagnostics: Vec::new() };
+    match serde_yaml::from_str::<Manuscript>(content) {
+        Ok(manuscript) => check(&manuscript, catalog, &locator, &mut report),
+        Err(e) => {
+            let at = e.location().map(|location| (location.line(), location.column())).unwrap_or((1, 1));
+            report.push(Severity::Error, at, e.to_string());
+        }
+    }
+    let mut diagnostics = report.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+}
+
+struct Report<'a> {
+    path: &'a Path,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Report<'_> {
+    fn push(&mut self, severity: Severity, (line, column): (usize, usize), message: String) {
+        self.diagnostics.push(Diagnostic { path: self.path.to_path_buf(), line, column, severity, message });
+    }
+
+    fn error(&mut self, at: (usize, usize), message: String) {
+        self.push(Severity::Error, at, message);
+    }
+
+    // SQL 中的问题按单词出现的次数映射回文件中的位置
+    fn sql_issues(&mut self, locator: &Locator, sql: &str, issues: Vec<SqlIssue>, lines: Range<usize>, fallback: (usize, usize)) {
+        for issue in issues {
+            let nth = word_offsets(sql, &issue.word).iter().take_while(|&&offset| offset < issue.offset).count();
+            let at = locator.word(lines.clone(), &issue.word, nth).unwrap_or(fallback);
+            self.push(issue.severity, at, issue.message);
+        }
+    }
+}
+
+fn check(manuscript: &Manuscript, catalog: &Catalog, locator: &Locator, report: &mut Report) {
+    let top = |key: &str| locator.top_level(key).map(|line| (line + 1, 1)).unwrap_or((1, 1));
+    if manuscript.name.trim().is_empty() {
+        report.error(top("name"), "name is missing".to_string());
+    }
+    if manuscript.sources.is_empty() {
+        report.error(top("sources"), "no sources defined".to_string());
+    }
+    if manuscript.sinks.is_empty() {
+        report.error(top("sinks"), "no sinks defined".to_string());
+    }
+
+    // transform 可以引用 source 和其他 transform；只有 dataset source 的字段是已知的
+    let mut tables: HashMap<&str, Option<&CatalogTable>> = HashMap::new();
+    let mut defined = HashSet::new();
+    for source in &manuscript.sources {
+        let item = locator.item("sources", &source.name);
+        let at_item = item.as_ref().map(|item| locator.start(item)).unwrap_or_else(|| top("sources"));
+        if !defined.insert(source.name.as_str()) {
+            report.error(at_item, format!("'{}' is defined more than once", source.name));
+        }
+        let dataset_at = item.as_ref().and_then(|item| locator.field(item.clone(), "dataset")).unwrap_or(at_item);
+
+        let mut table = None;
+        if source.kind == "dataset" {
+            match source.chain_table() {
+                None => report.error(dataset_at, format!("source '{}': dataset must be <chain>.<table>", source.name)),
+                Some(_) if catalog.is_empty() => {}
+                Some((chain, name)) => match catalog.chain_index(chain) {
+                    None => report.error(dataset_at, format!("unknown chain '{}'", chain)),
+                    Some(chain_index) => {
+                        table = catalog.find(chain, name);
+                        if table.is_none() {
+                            let at = (dataset_at.0, dataset_at.1 + chain.chars().count() + 1);
+                            let mut message = format!("chain '{}' has no table '{}'", chain, name);
+                            if let Some(similar) = catalog.tables(chain_index).iter().find(|t| t.name.eq_ignore_ascii_case(name)) {
+                                message.push_str(&format!(", did you mean '{}'?", similar.name));
+                            }
+                            report.error(at, message);
+                        }
+                    }
+                },
+            }
+        }
+        tables.insert(&source.name, table);
+
+        if let (Some(filter), Some(item)) = (&source.filter, &item) {
+            let issues = check_sql(filter, &tables, table);
+            let lines = locator.field(item.clone(), "filter").map(|(line, _)| line - 1).unwrap_or(item.start)..item.end;
+            report.sql_issues(locator, filter, issues, lines, dataset_at);
+        }
+    }
+
+    for transform in &manuscript.transforms {
+        tables.insert(&transform.name, None);
+    }
+    for transform in &manuscript.transforms {
+        let item = locator.item("transforms", &transform.name);
+        let at_item = item.as_ref().map(|item| locator.start(item)).unwrap_or_else(|| top("transforms"));
+        if !defined.insert(transform.name.as_str()) {
+            report.error(at_item, format!("'{}' is defined more than once", transform.name));
+        }
+        if transform.sql.trim().is_empty() {
+            report.error(at_item, format!("transform '{}' has no SQL", transform.name));
+            continue;
+        }
+        if let Some(item) = &item {
+            let issues = check_sql(&transform.sql, &tables, None);
+            let lines = locator.field(item.clone(), "sql").map(|(line, _)| line - 1).unwrap_or(item.start)..item.end;
+            report.sql_issues(locator, &transform.sql, issues, lines, at_item);
+        }
+    }
+
+    for sink in &manuscript.sinks {
+        let item = locator.item("sinks", &sink.name);
+        let at_item = item.as_ref().map(|item| locator.start(item)).unwrap_or_else(|| top("sinks"));
+        match &sink.from {
+            Some(from) if !defined.contains(from.as_str()) => {
+                let at = item.and_then(|item| locator.field(item, "from")).unwrap_or(at_item);
+                report.error(at, format!("sink '{}' reads from unknown '{}'", sink.name, from));
+            }
+            None => report.error(at_item, format!("sink '{}' has no 'from'", sink.name)),
+            _ => {}
+        }
+    }
+}
+
+// SQL 中的一个问题；offset 为 word 在 SQL 文本中的字节位置
+struct SqlIssue {
+    offset: usize,
+    word: String,
+    severity: Severity,
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TypeClass {
+    Number,
+    Text,
+    Boolean,
+    Time,
+    Other,
+}
+
+impl TypeClass {
+    // dataDictionary 中的类型名，例如 bigint、varchar(42)、decimal(38,0)、timestamp(3)
+    fn of(data_type: &str) -> Self {
+        let data_type = data_type.to_ascii_lowercase();
+        let base = data_type.split(['(', ' ']).next().unwrap_or_default();
+        match base {
+            "interval" => TypeClass::Other,
+            "decimal" | "numeric" | "double" | "float" | "real" | "number" => TypeClass::Number,
+            _ if base.starts_with("int") || base.starts_with("uint") || base.ends_with("int") => TypeClass::Number,
+            "varchar" | "char" | "string" | "text" | "json" => TypeClass::Text,
+            "bool" | "boolean" => TypeClass::Boolean,
+            "timestamp" | "date" | "time" | "datetime" => TypeClass::Time,
+            _ => TypeClass::Other,
+        }
+    }
+
+    fn of_literal(token: &Token, sql: &str) -> Option<Self> {
+        match token.kind {
+            TokenKind::Number => Some(TypeClass::Number),
+            TokenKind::String => Some(TypeClass::Text),
+            TokenKind::Keyword if token.is_keyword(sql, "TRUE") || token.is_keyword(sql, "FALSE") => Some(TypeClass::Boolean),
+            _ => None,
+        }
+    }
+
+    // 时间类型的字段常和字符串字面量比较，不算错误
+    fn compatible(self, literal: Self) -> bool {
+        self == literal
+            || self == TypeClass::Other
+            || (self == TypeClass::Time && literal == TypeClass::Text)
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            TypeClass::Number => "a number",
+            TypeClass::Text => "a string",
+            TypeClass::Boolean => "a boolean",
+            TypeClass::Time | TypeClass::Other => "a value",
+        }
+    }
+}
+
+const COMPARISONS: &[&str] = &["=", "<>", "!=", "<", ">", "<=", ">="];
+
+// 检查 SQL 引用的表和字段。implicit 为没有 FROM 的表达式（source 的 filter）所属的表
+fn check_sql(sql: &str, tables: &HashMap<&str, Option<&CatalogTable>>, implicit: Option<&CatalogTable>) -> Vec<SqlIssue> {
+    let tokens: Vec<Token> = lexer::tokenize(sql).into_iter().filter(|token| token.kind != TokenKind::Comment).collect();
+    let is = |i: usize, text: &str| tokens.get(i).is_some_and(|token| token.is(sql, text));
+    let is_keyword = |i: usize, keyword: &str| tokens.get(i).is_some_and(|token| token.is_keyword(sql, keyword));
+    let is_identifier = |i: usize| tokens.get(i).is_some_and(Token::is_identifier);
+    let mut issues = Vec::new();
+    let issue = |token: &Token, severity: Severity, message: String| SqlIssue {
+        offset: token.span.start,
+        word: token.name(sql).to_string(),
+        severity,
+        message,
+    };
+
+    // 紧跟在表达式后面的是省略了 AS 的别名，例如 count(*) cnt
+    let follows_expression = |i: usize| i > 0 && (
+        is(i - 1, ")")
+            || is_keyword(i - 1, "END")
+            || matches!(tokens[i - 1].kind, TokenKind::Identifier | TokenKind::QuotedIdentifier | TokenKind::Number | TokenKind::String)
+    );
+
+    // AS 定义的别名、省略 AS 的别名和 WITH 定义的子查询名
+    let mut aliases = HashSet::new();
+    let mut skip = HashSet::new();
+    for i in 0..tokens.len() {
+        if is_identifier(i) && follows_expression(i) {
+            aliases.insert(tokens[i].name(sql));
+        }
+        if is_keyword(i, "AS") && is_identifier(i + 1) {
+            aliases.insert(tokens[i + 1].name(sql));
+            skip.insert(i + 1);
+        }
+        if is_identifier(i) && is_keyword(i + 1, "AS") && is(i + 2, "(") {
+            aliases.insert(tokens[i].name(sql));
+            skip.insert(i);
+        }
+    }
+
+    // 是否在函数调用的括号内：这里的 FROM 不是表，例如 EXTRACT(YEAR FROM ts)、TRIM(LEADING '0' FROM s)
+    let mut in_call = HashSet::new();
+    let mut parens: Vec<bool> = Vec::new();
+    for i in 0..tokens.len() {
+        if is(i, ")") {
+            parens.pop();
+        }
+        if parens.last() == Some(&true) {
+            in_call.insert(i);
+        }
+        if is(i, "(") {
+            parens.push(i > 0 && is_identifier(i - 1));
+        }
+    }
+
+    // FROM / JOIN 后面的表及其别名
+    let mut scope: HashMap<&str, Option<&CatalogTable>> = HashMap::new();
+    let mut unknown_columns = false;  // 有子查询或字段未知的表时不检查不带前缀的字段
+    if let Some(table) = implicit {
+        scope.insert(table.name.as_str(), Some(table));
+    }
+    for i in 0..tokens.len() {
+        if in_call.contains(&i) || !(is_keyword(i, "FROM") || is_keyword(i, "JOIN")) {
+            continue;
+        }
+        let mut j = i + 1;
+        loop {
+            if !is_identifier(j) {
+                unknown_columns |= is(j, "(");
+                break;
+            }
+            let start = j;
+            while is(j + 1, ".") && is_identifier(j + 2) {
+                j += 2;
+            }
+            let name = &sql[tokens[start].span.start..tokens[j].span.end];
+            skip.extend(start..=j);
+            let table = match tables.get(name) {
+                Some(table) => *table,
+                None if aliases.contains(name) => None,
+                None => {
+                    issues.push(issue(&tokens[start], Severity::Error, format!(
+                        "table '{}' is not defined in sources or transforms", name
+                    )));
+                    None
+                }
+            };
+            unknown_columns |= table.is_none();
+            scope.insert(name, table);
+
+            j += 1;
+            if is_keyword(j, "AS") {
+                j += 1;
+            }
+            if is_identifier(j) {
+                scope.insert(tokens[j].name(sql), table);
+                skip.insert(j);
+                j += 1;
+            }
+            if !is(j, ",") {
+                break;
+            }
+            j += 1;
+        }
+    }
+
+    let mut candidates: Vec<&CatalogTable> = Vec::new();
+    for table in scope.values().flatten() {
+        if !candidates.iter().any(|candidate| candidate.id == table.id) {
+            candidates.push(table);
+        }
+    }
+
+    // 字段引用及其在 tokens 中的范围，用于检查比较两边的类型
+    let mut columns: Vec<(usize, usize, &DataDictionaryItem)> = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if !token.is_identifier() || skip.contains(&i) || is(i + 1, "(") || (i > 0 && is(i - 1, ".")) {
+            continue;
+        }
+        let name = token.name(sql);
+
+        // alias.column
+        if is(i + 1, ".") && is_identifier(i + 2) {
+            let column = &tokens[i + 2];
+            match scope.get(name) {
+                Some(Some(table)) => match table.columns.iter().find(|c| c.name == column.name(sql)) {
+                    Some(item) => columns.push((i, i + 2, item)),
+                    None => issues.push(issue(column, Severity::Error, format!(
+                        "table '{}' has no column '{}'", table.id, column.name(sql)
+                    ))),
+                },
+                Some(None) => {}
+                None if aliases.contains(name) => {}
+                None => issues.push(issue(token, Severity::Error, format!("unknown table or alias '{}'", name))),
+            }
+            continue;
+        }
+
+        if follows_expression(i) || aliases.contains(name) || scope.contains_key(name) || unknown_columns || candidates.is_empty() {
+            continue;
+        }
+        match candidates.iter().find_map(|table| table.columns.iter().find(|c| c.name == name)) {
+            Some(item) => columns.push((i, i, item)),
+            None => {
+                let mut names: Vec<String> = candidates.iter().map(|table| table.id.to_string()).collect();
+                names.sort();
+                issues.push(issue(token, Severity::Error, format!(
+                    "unknown column '{}' (not in {})", name, names.join(", ")
+                )));
+            }
+        }
+    }
+
+    // column <op> literal 或 literal <op> column
+    for (first, last, item) in columns {
+        let class = TypeClass::of(&item.dataType);
+        let is_comparison = |i: usize| COMPARISONS.iter().any(|op| is(i, op));
+        let mut literals = Vec::new();
+        if is_comparison(last + 1) {
+            literals.extend(tokens.get(last + 2));
+        }
+        if first >= 2 && is_comparison(first - 1) {
+            literals.push(&tokens[first - 2]);
+        }
+        for literal in literals {
+            if let Some(literal_class) = TypeClass::of_literal(literal, sql).filter(|&literal| !class.compatible(literal)) {
+                issues.push(issue(&tokens[last], Severity::Warning, format!(
+                    "column '{}' is {} but is compared with {}", item.name, item.dataType, literal_class.describe()
+                )));
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.offset);
+    issues
+}
+
+// 完整单词出现的字节位置，前后不能是字母、数字或下划线
+fn word_offsets(text: &str, word: &str) -> Vec<usize> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word)
+        .filter(|(offset, _)| {
+            let before = text[..*offset].chars().next_back();
+            let after = text[offset + word.len()..].chars().next();
+            !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+        })
+        .map(|(offset, _)| offset)
+        .collect()
+}
+
+// 在 YAML 文本中按行查找 key 和列表项的位置，serde_yaml 解析出的值不带位置信息
+struct Locator<'a> {
+    lines: Vec<&'a str>,
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches(|c| c == '"' || c == '\'')
+}
+
+impl<'a> Locator<'a> {
+    fn new(content: &'a str) -> Self {
+        Self { lines: content.lines().collect() }
+    }
+
+    fn is_top_level_key(line: &str) -> bool {
+        indent(line) == 0 && !line.is_empty() && !line.starts_with(['#', '-']) && line.contains(':')
+    }
+
+    // 行号从 0 开始
+    fn top_level(&self, key: &str) -> Option<usize> {
+        self.lines.iter().position(|line| {
+            Self::is_top_level_key(line) && line.split(':').next().map(str::trim_end) == Some(key)
+        })
+    }
+
+    fn section(&self, key: &str) -> Option<Range<usize>> {
+        let start = self.top_level(key)? + 1;
+        let end = (start..self.lines.len())
+            .find(|&i| Self::is_top_level_key(self.lines[i]))
+            .unwrap_or(self.lines.len());
+        Some(start..end)
+    }
+
+    // section 中名为 name 的列表项所占的行
+    fn item(&self, section: &str, name: &str) -> Option<Range<usize>> {
+        let section = self.section(section)?;
+        let starts: Vec<usize> = section.clone()
+            .filter(|&i| self.lines[i].trim_start().starts_with('-'))
+            .collect();
+        let item_indent = starts.first().map(|&i| indent(self.lines[i]))?;
+        let starts: Vec<usize> = starts.into_iter().filter(|&i| indent(self.lines[i]) == item_indent).collect();
+        starts.iter().enumerate()
+            .map(|(n, &start)| start..starts.get(n + 1).copied().unwrap_or(section.end))
+            .find(|item| self.value(item.clone(), "name").is_some_and(|value| unquote(value) == name))
+    }
+
+    fn start(&self, item: &Range<usize>) -> (usize, usize) {
+        let line = self.lines.get(item.start).copied().unwrap_or_default();
+        (item.start + 1, indent(line) + 1)
+    }
+
+    // 返回 key 所在的行（从 0 开始）、值的字节位置和值
+    fn find_field(&self, lines: Range<usize>, key: &str) -> Option<(usize, usize, &'a str)> {
+        lines.into_iter().find_map(|i| {
+            let line = self.lines.get(i)?;
+            let rest = line.trim_start();
+            let rest = rest.strip_prefix("- ").map(str::trim_start).unwrap_or(rest);
+            let value = rest.strip_prefix(key)?.strip_prefix(':')?;
+            let key_start = line.len() - rest.len();
+            let trimmed = value.trim_start();
+            let value_start = if trimmed.is_empty() { key_start } else { line.len() - trimmed.len() };
+            Some((i, value_start, trimmed))
+        })
+    }
+
+    fn value(&self, lines: Range<usize>, key: &str) -> Option<&'a str> {
+        self.find_field(lines, key).map(|(_, _, value)| value)
+    }
+
+    // 值的位置（行、列从 1 开始）
+    fn field(&self, lines: Range<usize>, key: &str) -> Option<(usize, usize)> {
+        let (i, start, _) = self.find_field(lines, key)?;
+        Some((i + 1, self.lines[i][..start].chars().count() + 1))
+    }
+
+    // 第 nth 次（从 0 开始）出现的完整单词
+    fn word(&self, lines: Range<usize>, word: &str, nth: usize) -> Option<(usize, usize)> {
+        lines.into_iter()
+            .filter_map(|i| self.lines.get(i).map(|line| (i, *line)))
+            .flat_map(|(i, line)| word_offsets(line, word).into_iter().map(move |offset| (i, line, offset)))
+            .nth(nth)
+            .map(|(i, line, offset)| (i + 1, line[..offset].chars().count() + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use crate::app::Chain;
+    use crate::catalog::TableSort;
+    use super::*;
+
+    fn column(name: &str, data_type: &str) -> DataDictionaryItem {
+        DataDictionaryItem { name: name.to_string(), dataType: data_type.to_string(), description: String::new() }
+    }
+
+    fn catalog() -> Catalog {
+        let mut tables = IndexMap::new();
+        tables.insert("blocks".to_string(), vec![
+            column("block_number", "bigint"),
+            column("hash", "varchar"),
+            column("block_timestamp", "timestamp"),
+        ]);
+        tables.insert("transactionLogs".to_string(), vec![column("address", "varchar"), column("block_number", "bigint")]);
+        let chain = Chain {
+            name: "Ethereum".to_string(),
+            status: "Online".to_string(),
+            lastUpdate: String::new(),
+            time_ago: String::new(),
+            dataDictionary: tables,
+        };
+        Catalog::build(&[chain], TableSort::Api)
+    }
+
+    fn manuscript(dataset: &str, sql: &str) -> String {
+        let sql: String = sql.lines().map(|line| format!("      {}\n", line)).collect();
+        format!(
+            "name: demo\nsources:\n  - name: blocks\n    type: dataset\n    dataset: {}\ntransforms:\n  - name: t\n    sql: |\n{}sinks:\n  - name: out\n    type: print\n    from: t\n",
+            dataset, sql
+        )
+    }
+
+    fn diagnostics(content: &str) -> Vec<(usize, usize, Severity, String)> {
+        validate(Path::new("m.yaml"), content, &catalog()).into_iter()
+            .map(|d| (d.line, d.column, d.severity, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn valid_manuscript_has_no_diagnostics() {
+        let content = manuscript("ethereum.blocks", "SELECT block_number, hash FROM blocks WHERE block_number > 100");
+        assert!(diagnostics(&content).is_empty());
+    }
+
+    #[test]
+    fn unknown_chain_points_at_the_dataset() {
+        let content = manuscript("solana.blocks", "SELECT * FROM blocks");
+        assert_eq!(diagnostics(&content), vec![(5, 14, Severity::Error, "unknown chain 'solana'".to_string())]);
+    }
+
+    #[test]
+    fn unknown_table_suggests_a_case_insensitive_match() {
+        let content = manuscript("ethereum.transactionlogs", "SELECT * FROM blocks");
+        assert_eq!(diagnostics(&content), vec![(
+            5, 23, Severity::Error,
+            "chain 'ethereum' has no table 'transactionlogs', did you mean 'transactionLogs'?".to_string(),
+        )]);
+    }
+
+    #[test]
+    fn unknown_column_is_reported_at_its_line_and_column_in_the_sql_block() {
+        let content = manuscript("ethereum.blocks", "SELECT block_number,\n       gas_used\nFROM blocks");
+        assert_eq!(diagnostics(&content), vec![(
+            10, 14, Severity::Error,
+            "unknown column 'gas_used' (not in ethereum.blocks)".to_string(),
+        )]);
+    }
+
+    #[test]
+    fn qualified_unknown_column_names_the_table() {
+        let content = manuscript("ethereum.blocks", "SELECT b.nonce FROM blocks b");
+        assert_eq!(diagnostics(&content), vec![(
+            9, 16, Severity::Error,
+            "table 'ethereum.blocks' has no column 'nonce'".to_string(),
+        )]);
+    }
+
+    #[test]
+    fn undefined_table_in_from() {
+        let content = manuscript("ethereum.blocks", "SELECT * FROM txs");
+        assert_eq!(diagnostics(&content), vec![(
+            9, 21, Severity::Error,
+            "table 'txs' is not defined in sources or transforms".to_string(),
+        )]);
+    }
+
+    #[test]
+    fn aliases_and_with_queries_are_not_columns() {
+        let content = manuscript("ethereum.blocks", "\
+WITH recent AS (SELECT block_number AS n, hash h FROM blocks b WHERE b.block_number > 10)
+SELECT r.n, h, count(*) cnt FROM recent r GROUP BY r.n, h ORDER BY cnt");
+        assert!(diagnostics(&content).is_empty());
+    }
+
+    #[test]
+    fn comparing_a_column_with_a_literal_of_another_type_warns() {
+        let content = manuscript("ethereum.blocks", "SELECT * FROM blocks WHERE block_number = 'latest' AND hash = 1");
+        assert_eq!(diagnostics(&content), vec![
+            (9, 34, Severity::Warning, "column 'block_number' is bigint but is compared with a string".to_string()),
+            (9, 62, Severity::Warning, "column 'hash' is varchar but is compared with a number".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn timestamps_can_be_compared_with_strings() {
+        let content = manuscript("ethereum.blocks", "SELECT * FROM blocks WHERE block_timestamp > '2024-01-01'");
+        assert!(diagnostics(&content).is_empty());
+    }
+
+    #[test]
+    fn from_inside_a_function_call_is_not_a_table() {
+        let content = manuscript("ethereum.blocks", "\
+SELECT EXTRACT(YEAR FROM block_timestamp) AS y,
+       SUBSTRING(hash FROM 1 FOR 3) AS prefix,
+       TRIM(LEADING '0' FROM hash) AS trimmed
+FROM blocks");
+        assert!(diagnostics(&content).is_empty());
+    }
+
+    #[test]
+    fn unknown_column_inside_extract_is_still_reported() {
+        let content = manuscript("ethereum.blocks", "SELECT EXTRACT(YEAR FROM mined_at) FROM blocks");
+        assert_eq!(diagnostics(&content), vec![(
+            9, 32, Severity::Error,
+            "unknown column 'mined_at' (not in ethereum.blocks)".to_string(),
+        )]);
+    }
+
+    #[test]
+    fn yaml_error_is_reported_at_the_parser_position() {
+        let content = "name: demo\nsources:\n  - name: blocks: x\n";
+        let diagnostics = diagnostics(content);
+        assert_eq!(diagnostics.len(), 1);
+        let (line, column, severity, message) = &diagnostics[0];
+        assert_eq!((*line, *column, *severity), (3, 17, Severity::Error));
+        assert!(message.starts_with("mapping values are not allowed"), "{}", message);
+    }
+
+    #[test]
+    fn missing_sections_and_unknown_sink_input() {
+        let content = "name: demo\nsources: []\nsinks:\n  - name: out\n    type: print\n    from: nowhere\n";
+        assert_eq!(diagnostics(content), vec![
+            (2, 1, Severity::Error, "no sources defined".to_string()),
+            (6, 11, Severity::Error, "sink 'out' reads from unknown 'nowhere'".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,410 @@
+// EngineClient 对着临时目录里的假 Docker socket 运行
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use base64::{engine::general_purpose::{STANDARD, URL_SAFE}, Engine};
+use ms::engine::{ContainerSpec, DockerHost, EngineClient, EngineError, PullEvent, RegistryAuth};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+};
+
+#[derive(Debug, Clone)]
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+}
+
+// 多于一块时用 chunked 编码分开发送，用来测试跨块的 JSON 行和日志帧
+struct Reply {
+    status: u16,
+    chunks: Vec<Vec<u8>>,
+}
+
+impl Reply {
+    fn json(status: u16, body: &str) -> Self {
+        Self { status, chunks: vec![body.as_bytes().to_vec()] }
+    }
+
+    fn empty(status: u16) -> Self {
+        Self { status, chunks: Vec::new() }
+    }
+}
+
+type Handler = fn(&Request) -> Reply;
+
+struct MockEngine {
+    dir: PathBuf,
+    socket: PathBuf,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockEngine {
+    fn start(name: &str, handler: Handler) -> Self {
+        let dir = std::env::temp_dir().join(format!("ms-engine-test-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("docker.sock");
+        std::fs::remove_file(&socket).ok();
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                serve(stream, handler, recorded.clone()).await;
+            }
+        });
+        Self { dir, socket, requests }
+    }
+
+    fn client(&self) -> EngineClient {
+        EngineClient::new(DockerHost::Unix(self.socket.clone()))
+    }
+
+    fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockEngine {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+// 每个连接处理一个请求
+async fn serve(stream: UnixStream, handler: Handler, recorded: Arc<Mutex<Vec<Request>>>) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).await.unwrap();
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).await.unwrap();
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let length = headers.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.parse().unwrap())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.unwrap();
+
+    let request = Request { method, path, headers, body: String::from_utf8(body).unwrap() };
+    let reply = handler(&request);
+    recorded.lock().unwrap().push(request);
+
+    let mut stream = reader.into_inner();
+    let head = format!("HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nConnection: close\r\n", reply.status);
+    if reply.chunks.len() <= 1 {
+        let body = reply.chunks.concat();
+        stream.write_all(format!("{}Content-Length: {}\r\n\r\n", head, body.len()).as_bytes()).await.unwrap();
+        stream.write_all(&body).await.unwrap();
+    } else {
+        stream.write_all(format!("{}Transfer-Encoding: chunked\r\n\r\n", head).as_bytes()).await.unwrap();
+        for chunk in &reply.chunks {
+            stream.write_all(format!("{:x}\r\n", chunk.len()).as_bytes()).await.unwrap();
+            stream.write_all(chunk).await.unwrap();
+            stream.write_all(b"\r\n").await.unwrap();
+            stream.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        stream.write_all(b"0\r\n\r\n").await.unwrap();
+    }
+    stream.shutdown().await.ok();
+}
+
+// 多路复用日志流的一帧：流类型、3 字节填充、4 字节大端长度
+fn log_frame(stream: u8, text: &str) -> Vec<u8> {
+    let mut frame = vec![stream, 0, 0, 0];
+    frame.extend_from_slice(&(text.len() as u32).to_be_bytes());
+    frame.extend_from_slice(text.as_bytes());
+    frame
+}
+
+#[tokio::test]
+async fn version_is_decoded() {
+    let engine = MockEngine::start("version", |request| match request.path.as_str() {
+        "/version" => Reply::json(200, r#"{"Version": "27.3.1", "ApiVersion": "1.47", "Os": "linux"}"#),
+        _ => Reply::json(404, r#"{"message": "page not found"}"#),
+    });
+    let version = engine.client().version().await.unwrap();
+    assert_eq!(version.version, "27.3.1");
+    assert_eq!(version.api_version, "1.47");
+    assert_eq!(engine.requests()[0].method, "GET");
+}
+
+#[tokio::test]
+async fn pull_streams_progress_events() {
+    let engine = MockEngine::start("pull", |_| Reply {
+        status: 200,
+        chunks: vec![
+            br#"{"status": "Pulling from chainbase/manuscript-debug", "id": "latest"}"#.to_vec(),
+            // 一行 JSON 被拆在两块里
+            b"\n{\"status\": \"Downloading\", \"id\": \"a1b2\", \"progressDetail\": {\"current\": 512, ".to_vec(),
+            b"\"total\": 1024}}\n".to_vec(),
+            b"{\"status\": \"Download complete\", \"id\": \"a1b2\", \"progressDetail\": {}}\n".to_vec(),
+        ],
+    });
+    let mut events: Vec<PullEvent> = Vec::new();
+    engine.client()
+        .pull_image("registry.example.com:5000/chainbase/manuscript-debug:v1", None, |event| events.push(event))
+        .await
+        .unwrap();
+
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[1].id.as_deref(), Some("a1b2"));
+    assert_eq!(events[1].progress_detail.current, Some(512));
+    assert_eq!(events[1].progress_detail.total, Some(1024));
+    assert_eq!(events[2].status, "Download complete");
+
+    let request = &engine.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/images/create?fromImage=registry.example.com%3A5000%2Fchainbase%2Fmanuscript-debug&tag=v1");
+}
+
+#[tokio::test]
+async fn pull_error_in_the_stream_fails_the_pull() {
+    let engine = MockEngine::start("pull-error", |_| Reply {
+        status: 200,
+        chunks: vec![
+            b"{\"status\": \"Pulling from chainbase/manuscript-debug\", \"id\": \"v9\"}\n".to_vec(),
+            b"{\"errorDetail\": {\"message\": \"manifest for chainbase/manuscript-debug:v9 not found\"}, \"error\": \"manifest for chainbase/manuscript-debug:v9 not found\"}\n".to_vec(),
+        ],
+    });
+    let mut events = 0;
+    let error = engine.client()
+        .pull_image("chainbase/manuscript-debug:v9", None, |_| events += 1)
+        .await
+        .unwrap_err();
+
+    assert_eq!(events, 1);
+    match error {
+        EngineError::Api { status, message } => {
+            assert_eq!(status, 200);
+            assert_eq!(message, "manifest for chainbase/manuscript-debug:v9 not found");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+// 写一个临时的 ~/.docker/config.json
+fn docker_config(name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ms-engine-test-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.json");
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn pull_sends_registry_credentials() {
+    let config = docker_config("auth", &format!(
+        r#"{{"auths": {{
+            "https://index.docker.io/v1/": {{"auth": "{}"}},
+            "registry.example.com:5000": {{"auth": "{}"}}
+        }}}}"#,
+        STANDARD.encode("hubuser:hubpass"),
+        STANDARD.encode("me:s3cr:et"),
+    ));
+    let auth = RegistryAuth::from_config(&config, "registry.example.com:5000/chainbase/manuscript-debug:v1").await.unwrap().unwrap();
+    assert_eq!(auth.username.as_deref(), Some("me"));
+    assert_eq!(auth.password.as_deref(), Some("s3cr:et"));
+    assert_eq!(auth.serveraddress, "registry.example.com:5000");
+
+    // 没有 registry 部分的镜像使用 Docker Hub 的凭据
+    let hub = RegistryAuth::from_config(&config, "chainbase/manuscript-debug:latest").await.unwrap().unwrap();
+    assert_eq!(hub.username.as_deref(), Some("hubuser"));
+    assert!(RegistryAuth::from_config(&config, "ghcr.io/chainbase/manuscript-debug").await.unwrap().is_none());
+    std::fs::remove_dir_all(config.parent().unwrap()).ok();
+
+    let engine = MockEngine::start("pull-auth", |request| match request.header("X-Registry-Auth") {
+        Some(_) => Reply::json(200, "{\"status\": \"Status: Image is up to date\"}\n"),
+        None => Reply::json(200, "{\"error\": \"unauthorized: authentication required\"}\n"),
+    });
+    engine.client()
+        .pull_image("registry.example.com:5000/chainbase/manuscript-debug:v1", Some(&auth), |_| {})
+        .await
+        .unwrap();
+    let header = engine.requests()[0].header("X-Registry-Auth").unwrap().to_string();
+    let sent: serde_json::Value = serde_json::from_slice(&URL_SAFE.decode(header).unwrap()).unwrap();
+    assert_eq!(sent, serde_json::json!({"username": "me", "password": "s3cr:et", "serveraddress": "registry.example.com:5000"}));
+}
+
+#[tokio::test]
+async fn missing_docker_config_means_anonymous_pull() {
+    let path = std::env::temp_dir().join(format!("ms-engine-test-{}-none", std::process::id())).join("config.json");
+    assert!(RegistryAuth::from_config(&path, "chainbase/manuscript-debug").await.unwrap().is_none());
+
+    let config = docker_config("broken", "{ not json");
+    let error = RegistryAuth::from_config(&config, "chainbase/manuscript-debug").await.unwrap_err();
+    std::fs::remove_dir_all(config.parent().unwrap()).ok();
+    assert!(matches!(error, EngineError::Credentials(_)), "{:?}", error);
+}
+
+#[tokio::test]
+async fn create_sends_the_spec_and_returns_the_id() {
+    let engine = MockEngine::start("create", |request| match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/containers/create?name=ms-debug") => Reply::json(201, r#"{"Id": "c0ffee", "Warnings": []}"#),
+        _ => Reply::json(404, r#"{"message": "page not found"}"#),
+    });
+    let spec = ContainerSpec::new("chainbase/manuscript-debug:latest", &[(8083, 18083), (8081, 18081)]);
+    let id = engine.client().create_container("ms-debug", &spec).await.unwrap();
+    assert_eq!(id, "c0ffee");
+
+    let request = &engine.requests()[0];
+    assert_eq!(request.header("Content-Type"), Some("application/json"));
+    let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(body["Image"], "chainbase/manuscript-debug:latest");
+    assert_eq!(body["ExposedPorts"]["8083/tcp"], serde_json::json!({}));
+    assert_eq!(body["HostConfig"]["PortBindings"]["8081/tcp"][0]["HostPort"], "18081");
+    assert_eq!(body["HostConfig"]["AutoRemove"], false);
+}
+
+#[tokio::test]
+async fn create_conflict_is_an_api_error() {
+    let engine = MockEngine::start("create-conflict", |_| {
+        Reply::json(409, r#"{"message": "Conflict. The container name \"/ms-debug\" is already in use"}"#)
+    });
+    let spec = ContainerSpec::new("chainbase/manuscript-debug:latest", &[(8083, 18083)]);
+    match engine.client().create_container("ms-debug", &spec).await.unwrap_err() {
+        EngineError::Api { status, message } => {
+            assert_eq!(status, 409);
+            assert!(message.contains("already in use"), "{}", message);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn start_accepts_no_content() {
+    let engine = MockEngine::start("start", |request| match request.path.as_str() {
+        "/containers/c0ffee/start" => Reply::empty(204),
+        _ => Reply::json(404, r#"{"message": "page not found"}"#),
+    });
+    engine.client().start_container("c0ffee").await.unwrap();
+    assert_eq!(engine.requests()[0].method, "POST");
+}
+
+#[tokio::test]
+async fn inspect_decodes_state_and_ports() {
+    let engine = MockEngine::start("inspect", |request| match request.path.as_str() {
+        "/containers/ms-debug/json" => Reply::json(200, r#"{
+            "Id": "c0ffee",
+            "State": {"Status": "running", "Running": true, "ExitCode": 0, "StartedAt": "2026-10-18T03:00:00Z", "FinishedAt": "0001-01-01T00:00:00Z"},
+            "Config": {"Image": "chainbase/manuscript-debug:latest"},
+            "NetworkSettings": {"Ports": {
+                "8083/tcp": [{"HostIp": "0.0.0.0", "HostPort": "18083"}],
+                "9000/tcp": null
+            }}
+        }"#),
+        _ => Reply::json(404, r#"{"message": "No such container: missing"}"#),
+    });
+    let client = engine.client();
+    let container = client.inspect_container("ms-debug").await.unwrap().unwrap();
+    assert_eq!(container.id, "c0ffee");
+    assert!(container.state.running);
+    assert_eq!(container.config.image, "chainbase/manuscript-debug:latest");
+    let bindings = container.network_settings.ports["8083/tcp"].as_ref().unwrap();
+    assert_eq!(bindings[0].host_port, "18083");
+    assert!(container.network_settings.ports["9000/tcp"].is_none());
+
+    // 404 表示容器不存在
+    assert!(client.inspect_container("missing").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn logs_are_demultiplexed_into_lines() {
+    let engine = MockEngine::start("logs", |_| {
+        let stdout = log_frame(1, "starting sql gateway\nlistening on 8083\n");
+        let stderr = log_frame(2, "WARN slow ");
+        let rest = log_frame(2, "checkpoint\ntrailing");
+        Reply {
+            status: 200,
+            // 一帧的头和数据拆在两块里
+            chunks: vec![stdout[..5].to_vec(), stdout[5..].to_vec(), stderr, rest],
+        }
+    });
+    let mut logs = engine.client().follow_logs("c0ffee", 100).await.unwrap();
+    let mut lines = Vec::new();
+    while let Some(batch) = logs.next_lines().await.unwrap() {
+        lines.extend(batch);
+    }
+    assert_eq!(lines, ["starting sql gateway", "listening on 8083", "WARN slow checkpoint", "trailing"]);
+    assert_eq!(engine.requests()[0].path, "/containers/c0ffee/logs?follow=true&stdout=true&stderr=true&tail=100");
+}
+
+#[tokio::test]
+async fn missing_socket_is_a_connect_error() {
+    let socket = std::env::temp_dir().join(format!("ms-engine-test-{}-missing.sock", std::process::id()));
+    let client = EngineClient::new(DockerHost::Unix(socket.clone()));
+    match client.version().await.unwrap_err() {
+        EngineError::Connect(host, _) => assert_eq!(host, format!("unix://{}", socket.display())),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn docker_host_is_parsed() {
+    assert_eq!(DockerHost::parse("unix:///run/user/1000/docker.sock").unwrap(), DockerHost::Unix("/run/user/1000/docker.sock".into()));
+    assert_eq!(DockerHost::parse("tcp://127.0.0.1:2375/").unwrap(), DockerHost::Tcp("127.0.0.1:2375".into()));
+    assert!(matches!(DockerHost::parse("ssh://me@host"), Err(EngineError::UnsupportedHost(_))));
+}
+
+// 查询参数中的 filters 是百分号编码的 JSON
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            decoded.push(u8::from_str_radix(&value[i + 1..i + 3], 16).unwrap());
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).unwrap()
+}
+
+#[tokio::test]
+async fn container_names_are_escaped_in_filters_and_paths() {
+    let engine = MockEngine::start("escape", |request| {
+        if request.path.starts_with("/containers/json") {
+            Reply::json(200, r#"[{"Id": "c1", "State": "running"}]"#)
+        } else {
+            Reply::empty(200)
+        }
+    });
+    let client = engine.client();
+    let container = client.find_container("ms.debug").await.unwrap().unwrap();
+    assert_eq!(container.id, "c1");
+    let _ = client.follow_logs("ms debug/1", 10).await.unwrap();
+
+    let requests = engine.requests();
+    let filters = requests[0].path.strip_prefix("/containers/json?all=true&filters=").unwrap();
+    let filters = percent_decode(filters);
+    let filters: serde_json::Value = serde_json::from_str(&filters).unwrap();
+    assert_eq!(filters["name"][0], r"^/ms\.debug$");
+    assert_eq!(requests[1].path, "/containers/ms%20debug%2F1/logs?follow=true&stdout=true&stderr=true&tail=10");
+}
@@ -0,0 +1,227 @@
+// `ms jobs` 对着本地的 mock job manager 运行
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::PathBuf,
+    process::{Command, Output},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+#[derive(Debug, Clone)]
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+type Handler = fn(&Request) -> (u16, &'static str);
+
+// 每个连接处理一个请求，记录下来供断言使用
+struct MockJobManager {
+    port: u16,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockJobManager {
+    fn start(handler: Handler) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    break;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut parts = line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let request = Request { method, path, body: String::from_utf8(body).unwrap() };
+                let (status, response) = handler(&request);
+                recorded.lock().unwrap().push(request);
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, response.len(), response
+                ).unwrap();
+            }
+        });
+        Self { port, requests }
+    }
+
+    fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    // 空配置文件，避免读到用户自己的配置
+    fn run(&self, args: &[&str]) -> Output {
+        let dir = std::env::temp_dir().join(format!("ms-jobs-test-{}-{}", std::process::id(), self.port));
+        fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("config.toml");
+        fs::write(&config, "").unwrap();
+        let port = self.port.to_string();
+        let output = Command::new(env!("CARGO_BIN_EXE_ms"))
+            .args(["--config", config.to_str().unwrap(), "--job-port", &port, "jobs"])
+            .args(args)
+            .env_remove("MS_PROFILE")
+            .output()
+            .unwrap();
+        fs::remove_dir_all(&dir).ok();
+        output
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+const OVERVIEW: &str = r#"{"jobs": [
+    {"jid": "aaa0", "name": "finished_job", "state": "FINISHED", "start-time": 1700000000000, "end-time": 1700000060000, "duration": 60000},
+    {"jid": "bbb1", "name": "running_job", "state": "RUNNING", "start-time": 1700000100000, "end-time": -1, "duration": 3723000},
+    {"jid": "ccc2", "name": "failed_job", "state": "FAILED", "start-time": 1700000200000, "end-time": 1700000201000, "duration": 1000}
+]}"#;
+
+#[test]
+fn list_shows_running_failed_and_finished_jobs() {
+    let server = MockJobManager::start(|request| match request.path.as_str() {
+        "/jobs/overview" => (200, OVERVIEW),
+        _ => (404, r#"{"errors": ["Not found"]}"#),
+    });
+    let output = server.run(&["list"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let lines: Vec<String> = stdout(&output).lines().map(str::to_string).collect();
+    assert_eq!(lines.len(), 3);
+    // 运行中的在前，然后是失败的和已结束的
+    assert!(lines[0].starts_with("bbb1") && lines[0].contains("RUNNING") && lines[0].contains("1h 02m 03s"));
+    assert!(lines[1].starts_with("ccc2") && lines[1].contains("FAILED"));
+    assert!(lines[2].starts_with("aaa0") && lines[2].contains("FINISHED") && lines[2].ends_with("finished_job"));
+}
+
+#[test]
+fn submit_runs_the_manuscript_jar_with_the_file_content() {
+    let server = MockJobManager::start(|request| match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/jars") => (200, r#"{"address": "http://localhost:8081", "files": [
+            {"id": "1234_other.jar", "name": "other.jar"},
+            {"id": "5678_manuscript-runner.jar", "name": "manuscript-runner.jar"}
+        ]}"#),
+        ("POST", "/jars/5678_manuscript-runner.jar/run") => (200, r#"{"jobid": "d1e2f3"}"#),
+        _ => (404, r#"{"errors": ["Not found"]}"#),
+    });
+    let file: PathBuf = std::env::temp_dir().join(format!("ms-jobs-submit-{}.yaml", std::process::id()));
+    let content = "name: demo\nspecVersion: v1.0.0\nparallelism: 2\nsources: []\n";
+    fs::write(&file, content).unwrap();
+
+    let output = server.run(&["submit", file.to_str().unwrap()]);
+    fs::remove_file(&file).ok();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output).trim(), "d1e2f3");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    let body: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+    assert_eq!(body["programArgsList"], serde_json::json!(["--manuscript", content]));
+    assert_eq!(body["parallelism"], 2);
+}
+
+#[test]
+fn submit_fails_without_a_runner_jar() {
+    // 其他 jar 不能代替 manuscript runner
+    let server = MockJobManager::start(|_| (200, r#"{"address": "http://localhost:8081", "files": [
+        {"id": "1234_other.jar", "name": "other.jar"}
+    ]}"#));
+    let file: PathBuf = std::env::temp_dir().join(format!("ms-jobs-nojar-{}.yaml", std::process::id()));
+    fs::write(&file, "name: demo\n").unwrap();
+
+    let output = server.run(&["submit", file.to_str().unwrap()]);
+    fs::remove_file(&file).ok();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("No manuscript runner jar"));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn cancel_patches_the_job() {
+    let server = MockJobManager::start(|request| match (request.method.as_str(), request.path.as_str()) {
+        ("PATCH", "/jobs/bbb1?mode=cancel") => (202, "{}"),
+        _ => (404, r#"{"errors": ["Not found"]}"#),
+    });
+    let output = server.run(&["cancel", "bbb1"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Cancellation requested for job bbb1"));
+    assert_eq!(server.requests()[0].method, "PATCH");
+}
+
+#[test]
+fn cancel_reports_job_manager_errors() {
+    let server = MockJobManager::start(|_| {
+        (404, r#"{"errors": ["org.apache.flink.runtime.rest.NotFoundException: Job ffff not found\n\tat org.apache.flink.Foo"]}"#)
+    });
+    let output = server.run(&["cancel", "ffff"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = stderr(&output);
+    assert!(stderr.contains("404") && stderr.contains("Job ffff not found"), "{}", stderr);
+    // 只显示异常的第一行
+    assert!(!stderr.contains("org.apache.flink.Foo"));
+}
+
+#[test]
+fn exceptions_prints_root_exception_and_history() {
+    let server = MockJobManager::start(|request| match request.path.as_str() {
+        "/jobs/ccc2/exceptions" => (200, r#"{
+            "root-exception": "java.lang.RuntimeException: sink failed\n\tat Sink.write",
+            "timestamp": 1700000201000,
+            "all-exceptions": [],
+            "truncated": false,
+            "exceptionHistory": {"entries": [
+                {"exceptionName": "java.lang.RuntimeException", "stacktrace": "java.lang.RuntimeException: sink failed\n\tat Sink.write", "timestamp": 1700000201000, "taskName": "Sink: print", "location": null},
+                {"exceptionName": "java.io.IOException", "stacktrace": "java.io.IOException: connection reset\n\tat Source.read", "timestamp": 1700000200500, "taskName": "Source: blocks", "location": "localhost:6121"}
+            ], "truncated": false}
+        }"#),
+        _ => (404, r#"{"errors": ["Not found"]}"#),
+    });
+    let output = server.run(&["exceptions", "ccc2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let stdout = stdout(&output);
+    assert!(stdout.starts_with("Root exception"));
+    assert_eq!(stdout.matches("sink failed").count(), 1);
+    assert!(stdout.contains("java.io.IOException (") && stdout.contains("in Source: blocks at localhost:6121"));
+    assert!(stdout.contains("\tat Source.read"));
+}
+
+#[test]
+fn unreachable_job_manager_is_reported() {
+    // 绑定后立即关闭，端口上没有服务
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let server = MockJobManager { port, requests: Arc::default() };
+    let output = server.run(&["list"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains(&format!("Failed to reach job manager at http://127.0.0.1:{}", port)));
+}